@@ -0,0 +1,123 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// File name written into the output folder by `CsvWriter`
+pub const MESSAGES_CSV_FILE: &str = "messages.csv";
+
+/// Writes every record as one flat CSV row, `channel_name,username,timestamp,content`, in the
+/// order the service provides them, for spreadsheets. Fields containing commas, quotes or
+/// newlines are quoted, so multi-line messages stay in one cell
+pub struct CsvWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+    epoch_timestamps: bool,
+}
+
+impl CsvWriter {
+    pub fn new(output_folder: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new(output_folder)))
+    }
+
+    /// Creates a writer emitting `messages.csv` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(MESSAGES_CSV_FILE),
+            epoch_timestamps: false,
+        }
+    }
+
+    /// Writes the timestamp column as integer Unix seconds parsed from the raw timestamp (empty
+    /// when unparseable)
+    pub fn with_epoch_timestamps(mut self, epoch_timestamps: bool) -> Self {
+        self.epoch_timestamps = epoch_timestamps;
+        self
+    }
+
+    /// Writes to this path within the sink instead of `messages.csv`
+    pub fn with_output_path(mut self, output_path: String) -> Self {
+        self.output_path = PathBuf::from(output_path);
+        self
+    }
+}
+
+impl MarkdownWriter for CsvWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let mut csv = csv::Writer::from_writer(Vec::new());
+        csv.write_record(["channel_name", "username", "timestamp", "content"])?;
+        for record in records {
+            let timestamp = if self.epoch_timestamps {
+                record.epoch_seconds().map(|epoch| epoch.to_string()).unwrap_or_default()
+            } else {
+                record.timestamp.clone()
+            };
+            csv.write_record([&record.channel_name, &record.username, &timestamp, &record.content])?;
+        }
+        let output = csv.into_inner().map_err(|e| e.to_string())?;
+
+        self.sink.write_file(&self.output_path, &output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(channel: &str, username: &str, content: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: channel.to_string(),
+            username: username.to_string(),
+            timestamp: "2025-12-16 10:30:00 +00:00".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_awkward_content_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let writer = CsvWriter::new(dir.path().to_string_lossy().into_owned());
+        let awkward = "first line, with a comma\nsecond \"quoted\" line";
+        let records = vec![record("random", "bob", awkward), record("general", "alice", "hello")];
+
+        writer.write(&records).unwrap();
+
+        let mut reader = csv::Reader::from_path(dir.path().join(MESSAGES_CSV_FILE)).unwrap();
+        assert_eq!(reader.headers().unwrap(), vec!["channel_name", "username", "timestamp", "content"]);
+        let rows: Vec<csv::StringRecord> = reader.records().map(|row| row.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], vec!["random", "bob", "2025-12-16 10:30:00 +00:00", awkward]);
+        assert_eq!(rows[1], vec!["general", "alice", "2025-12-16 10:30:00 +00:00", "hello"]);
+    }
+
+    #[test]
+    fn test_epoch_timestamps_replace_the_formatted_ones() {
+        let dir = TempDir::new().unwrap();
+        let writer = CsvWriter::new(dir.path().to_string_lossy().into_owned()).with_epoch_timestamps(true);
+        let mut parsed = record("general", "alice", "hello");
+        parsed.raw_timestamp = "2025-12-16T10:30:00Z".to_string();
+        let mut unparseable = record("general", "bob", "yo");
+        unparseable.raw_timestamp = "garbage".to_string();
+
+        writer.write(&[parsed, unparseable]).unwrap();
+
+        let content = fs::read_to_string(dir.path().join(MESSAGES_CSV_FILE)).unwrap();
+        assert!(content.contains("general,alice,1765881000,hello\n"));
+        assert!(content.contains("general,bob,,yo\n"));
+    }
+
+    #[test]
+    fn test_no_records_write_only_the_header() {
+        let dir = TempDir::new().unwrap();
+
+        CsvWriter::new(dir.path().to_string_lossy().into_owned()).write(&[]).unwrap();
+
+        let content = fs::read_to_string(dir.path().join(MESSAGES_CSV_FILE)).unwrap();
+        assert_eq!(content, "channel_name,username,timestamp,content\n");
+    }
+}
@@ -0,0 +1,138 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use core::utils::sanitize_filename;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+pub mod template;
+
+pub use template::HtmlTemplate;
+
+use template::escape_html;
+
+/// Writes one HTML page per channel, `<channel>.html`, rendered through a template
+pub struct HtmlWriter {
+    sink: Arc<dyn OutputSink>,
+    template: HtmlTemplate,
+    header: String,
+}
+
+impl HtmlWriter {
+    pub fn new(output_folder: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new(output_folder)), HtmlTemplate::default())
+    }
+
+    /// Creates a writer emitting pages into the given sink with a custom template
+    pub fn with_sink(sink: Arc<dyn OutputSink>, template: HtmlTemplate) -> Self {
+        Self {
+            sink,
+            template,
+            header: String::new(),
+        }
+    }
+
+    /// Places a notice (e.g. license text) at the template's `{{header}}` placeholder on every page
+    pub fn with_header(mut self, header: Option<String>) -> Self {
+        self.header = header.map(|header| header.trim_end().to_string()).unwrap_or_default();
+        self
+    }
+}
+
+/// Renders a channel's messages as a sequence of `<article>` elements
+fn format_messages(records: &[&ExtractedRecord]) -> String {
+    records
+        .iter()
+        .map(|record| {
+            format!(
+                "<article class=\"message\">\n<header><strong>{}</strong><time>{}</time></header>\n<p>{}</p>\n</article>\n",
+                escape_html(&record.username),
+                escape_html(&record.timestamp),
+                escape_html(&record.content)
+            )
+        })
+        .collect()
+}
+
+impl MarkdownWriter for HtmlWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let mut by_channel: BTreeMap<&str, Vec<&ExtractedRecord>> = BTreeMap::new();
+        for record in records {
+            by_channel.entry(record.channel_name.as_str()).or_default().push(record);
+        }
+
+        for (channel_name, channel_records) in by_channel {
+            let filename = format!("{}.html", sanitize_filename(channel_name));
+            let page = self.template.render(channel_name, &self.header, &format_messages(&channel_records));
+            self.sink.write_file(Path::new(&filename), page.as_bytes())?;
+            log::debug!(file = filename.as_str(), count = channel_records.len(); "wrote html file");
+        }
+        Ok(())
+    }
+}
+
+/// Removes the pages an earlier run left at the top of `folder` and returns how many were removed
+/// Pages are named after channels, so every `.html` file there counts as one
+pub fn clean_html_pages(folder: &Path) -> Result<usize> {
+    let mut removed = 0;
+    if !folder.is_dir() {
+        return Ok(removed);
+    }
+
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && entry.file_name().to_string_lossy().ends_with(".html") {
+            fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_html_writer_renders_custom_template_per_channel() {
+        let dir = TempDir::new().unwrap();
+        let sink = Arc::new(DirectorySink::new(dir.path()));
+        let template = HtmlTemplate::parse("<h1>{{channel}}</h1>\n{{messages}}".to_string()).unwrap();
+        let records = vec![ExtractedRecord {
+            channel_name: "general".to_string(),
+            username: "alice".to_string(),
+            timestamp: "2025-12-16 10:30:00".to_string(),
+            content: "1 < 2".to_string(),
+            ..Default::default()
+        }];
+
+        HtmlWriter::with_sink(sink, template).write(&records).unwrap();
+
+        let page = fs::read_to_string(dir.path().join("general.html")).unwrap();
+        assert!(page.starts_with("<h1>general</h1>\n<article class=\"message\">"));
+        assert!(page.contains("<strong>alice</strong><time>2025-12-16 10:30:00</time>"));
+        assert!(page.contains("<p>1 &lt; 2</p>"));
+    }
+
+    #[test]
+    fn test_html_writer_puts_header_above_heading() {
+        let dir = TempDir::new().unwrap();
+        let sink = Arc::new(DirectorySink::new(dir.path()));
+        let records = vec![ExtractedRecord {
+            channel_name: "general".to_string(),
+            ..Default::default()
+        }];
+
+        HtmlWriter::with_sink(sink, HtmlTemplate::default())
+            .with_header(Some("CC BY-SA & friends\n".to_string()))
+            .write(&records)
+            .unwrap();
+
+        let page = fs::read_to_string(dir.path().join("general.html")).unwrap();
+        assert!(page.contains("<body>\n<div class=\"notice\">CC BY-SA &amp; friends</div>\n<h1>#general</h1>"));
+    }
+}
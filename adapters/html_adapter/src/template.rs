@@ -0,0 +1,133 @@
+use core::ports::Result;
+
+/// Placeholders every template must contain
+const REQUIRED_PLACEHOLDERS: [&str; 2] = ["channel", "messages"];
+
+/// Stylesheet injected at `{{style}}`
+pub const DEFAULT_STYLE: &str = "body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; \
+padding: 0 1rem; color: #1f2328; }
+h1 { border-bottom: 1px solid #d0d7de; padding-bottom: 0.5rem; }
+.message { padding: 0.75rem 0; border-bottom: 1px solid #eaeef2; }
+.message header { margin-bottom: 0.25rem; }
+.message time { color: #656d76; font-size: 0.875rem; margin-left: 0.5rem; }
+.message p { margin: 0; white-space: pre-wrap; }
+.notice { color: #656d76; font-size: 0.875rem; white-space: pre-wrap; }";
+
+/// Template used when none is configured
+pub const DEFAULT_TEMPLATE: &str = "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>#{{channel}}</title>
+<style>
+{{style}}
+</style>
+</head>
+<body>
+{{header}}<h1>#{{channel}}</h1>
+{{messages}}
+</body>
+</html>
+";
+
+/// A page template with `{{channel}}`, `{{messages}}` and optional `{{style}}` and `{{header}}` placeholders
+#[derive(Debug, Clone)]
+pub struct HtmlTemplate {
+    source: String,
+}
+
+impl Default for HtmlTemplate {
+    fn default() -> Self {
+        Self {
+            source: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl HtmlTemplate {
+    /// Checks that the required placeholders are present
+    pub fn parse(source: String) -> Result<Self> {
+        let missing: Vec<String> = REQUIRED_PLACEHOLDERS
+            .iter()
+            .filter(|name| !source.contains(&format!("{{{{{}}}}}", name)))
+            .map(|name| format!("{{{{{}}}}}", name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("HTML template is missing {}", missing.join(", ")).into());
+        }
+        Ok(Self { source })
+    }
+
+    /// Substitutes the placeholders in a single pass, so injected content is never re-expanded
+    /// Unknown placeholders are kept as written; an empty `header` renders nothing
+    pub fn render(&self, channel: &str, header: &str, messages: &str) -> String {
+        let mut output = String::with_capacity(self.source.len() + messages.len());
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                rest = &rest[start..];
+                break;
+            };
+            match &after[..end] {
+                "channel" => output.push_str(&escape_html(channel)),
+                "messages" => output.push_str(messages),
+                "style" => output.push_str(DEFAULT_STYLE),
+                "header" if !header.is_empty() => {
+                    output.push_str(&format!("<div class=\"notice\">{}</div>\n", escape_html(header)))
+                }
+                "header" => {}
+                other => {
+                    output.push_str("{{");
+                    output.push_str(other);
+                    output.push_str("}}");
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Escapes text for use in HTML element content and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_template_injects_content() {
+        let template = HtmlTemplate::parse(
+            "<title>{{channel}}</title><style>{{style}}</style><main>{{messages}}</main>{{footer}}".to_string(),
+        )
+        .unwrap();
+
+        let page = template.render("a&b", "", "<p>{{channel}}</p>");
+
+        assert!(page.starts_with("<title>a&amp;b</title><style>body {"));
+        assert!(page.ends_with("<main><p>{{channel}}</p></main>{{footer}}"));
+    }
+
+    #[test]
+    fn test_template_missing_placeholders_errors() {
+        let error = HtmlTemplate::parse("<main>{{channel}}</main>".to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "HTML template is missing {{messages}}");
+        assert!(HtmlTemplate::parse(DEFAULT_TEMPLATE.to_string()).is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// File name written into the output folder by `ChannelsJsonWriter`
+pub const CHANNELS_JSON_FILE: &str = "channels.json";
+
+/// Writes the messages of every channel as one JSON object keyed by channel name, each holding
+/// `{username, timestamp, content}` objects in record order; the machine-readable counterpart of
+/// the Markdown channel files, with the same localized timestamps
+pub struct ChannelsJsonWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+    epoch_timestamps: bool,
+}
+
+impl ChannelsJsonWriter {
+    pub fn new(output_folder: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new(output_folder)))
+    }
+
+    /// Creates a writer emitting `channels.json` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(CHANNELS_JSON_FILE),
+            epoch_timestamps: false,
+        }
+    }
+
+    /// Emits `timestamp` as integer Unix seconds parsed from the raw timestamp (null when unparseable)
+    pub fn with_epoch_timestamps(mut self, epoch_timestamps: bool) -> Self {
+        self.epoch_timestamps = epoch_timestamps;
+        self
+    }
+
+    /// Writes to this path within the sink instead of `channels.json`
+    pub fn with_output_path(mut self, output_path: String) -> Self {
+        self.output_path = PathBuf::from(output_path);
+        self
+    }
+}
+
+impl MarkdownWriter for ChannelsJsonWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let mut channels: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+        for record in records {
+            let timestamp = if self.epoch_timestamps {
+                record.epoch_seconds().into()
+            } else {
+                Value::from(record.timestamp.as_str())
+            };
+            channels.entry(&record.channel_name).or_default().push(json!({
+                "username": record.username,
+                "timestamp": timestamp,
+                "content": record.content,
+            }));
+        }
+
+        let json = serde_json::to_string_pretty(&channels)?;
+        self.sink.write_file(&self.output_path, json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(channel: &str, username: &str, content: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: channel.to_string(),
+            username: username.to_string(),
+            timestamp: "2025-12-16 10:30:00 UTC".to_string(),
+            raw_timestamp: "2025-12-16T10:30:00Z".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_messages_are_keyed_by_channel() {
+        let dir = TempDir::new().unwrap();
+        let writer = ChannelsJsonWriter::new(dir.path().to_string_lossy().into_owned());
+        let records = vec![record("random", "carol", "hey"), record("general", "alice", "hello"), record("random", "bob", "yo")];
+
+        writer.write(&records).unwrap();
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(dir.path().join(CHANNELS_JSON_FILE)).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            json!({
+                "general": [{"username": "alice", "timestamp": "2025-12-16 10:30:00 UTC", "content": "hello"}],
+                "random": [
+                    {"username": "carol", "timestamp": "2025-12-16 10:30:00 UTC", "content": "hey"},
+                    {"username": "bob", "timestamp": "2025-12-16 10:30:00 UTC", "content": "yo"},
+                ],
+            })
+        );
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_epoch_timestamps_are_numeric() {
+        let dir = TempDir::new().unwrap();
+        let writer = ChannelsJsonWriter::new(dir.path().to_string_lossy().into_owned()).with_epoch_timestamps(true);
+        let mut unparseable = record("general", "bob", "yo");
+        unparseable.raw_timestamp = "garbage".to_string();
+
+        writer.write(&[record("general", "alice", "hello"), unparseable]).unwrap();
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(dir.path().join(CHANNELS_JSON_FILE)).unwrap()).unwrap();
+        assert_eq!(written["general"][0]["timestamp"], Value::from(1_765_881_000));
+        assert_eq!(written["general"][1]["timestamp"], Value::Null);
+    }
+
+    #[test]
+    fn test_no_records_write_nothing() {
+        let dir = TempDir::new().unwrap();
+
+        ChannelsJsonWriter::new(dir.path().to_string_lossy().into_owned()).write(&[]).unwrap();
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
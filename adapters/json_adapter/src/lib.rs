@@ -0,0 +1,159 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use core::summary::build_summary_in;
+use core::utils::OutputTimezone;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub mod channels;
+pub mod records;
+pub mod repository;
+
+pub use channels::ChannelsJsonWriter;
+pub use records::JsonRecordsWriter;
+pub use repository::JsonDataRepository;
+
+/// Writes aggregate activity statistics as a single JSON document for dashboards
+pub struct SummaryJsonWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+    generated_at: Option<String>,
+    timezone: OutputTimezone,
+}
+
+impl SummaryJsonWriter {
+    pub fn new(output_path: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new("")), output_path)
+    }
+
+    /// Creates a summary writer emitting `output_path` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>, output_path: String) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(output_path),
+            generated_at: None,
+            timezone: OutputTimezone::default(),
+        }
+    }
+
+    /// Stamps the summary with a `generated_at` time (see `core::utils::resolve_generated_at`)
+    pub fn with_generated_at(mut self, generated_at: Option<String>) -> Self {
+        self.generated_at = generated_at;
+        self
+    }
+
+    /// Counts messages per month in this timezone instead of the local one
+    pub fn with_timezone(mut self, timezone: OutputTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+}
+
+impl MarkdownWriter for SummaryJsonWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let mut summary = build_summary_in(records, self.timezone);
+        summary.generated_at = self.generated_at.clone();
+
+        let json = serde_json::to_string_pretty(&summary)?;
+        self.sink.write_file(&self.output_path, json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(channel: &str, username: &str, timestamp: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: channel.to_string(),
+            username: username.to_string(),
+            timestamp: timestamp.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_json_fields() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("summary.json");
+        let writer = SummaryJsonWriter::new(path.to_string_lossy().into_owned());
+        let records = vec![
+            record("general", "alice", "2025-11-30 12:00:00"),
+            record("general", "bob", "2025-12-01 12:00:00"),
+            record("random", "alice", "2025-12-02 12:00:00"),
+        ];
+
+        writer.write(&records).unwrap();
+
+        // Channel ranges need timestamp parsing and are checked separately
+        let mut summary: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        summary.as_object_mut().unwrap().remove("channel_ranges");
+        assert_eq!(
+            summary,
+            json!({
+                "total_messages": 3,
+                "channels": {"general": 2, "random": 1},
+                "authors": {"alice": 2, "bob": 1},
+                "months": {"2025-11": 1, "2025-12": 2},
+                "date_range": {
+                    "first": "2025-11-30 12:00:00",
+                    "last": "2025-12-02 12:00:00"
+                },
+                "emoji_message_ratio": 0.0,
+                "emoji_per_message": 0.0,
+                "link_count": 0,
+                "duplicate_messages": 0,
+                "duplicate_clusters": 0
+            })
+        );
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_summary_json_channel_ranges() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("summary.json");
+        let writer = SummaryJsonWriter::new(path.to_string_lossy().into_owned());
+        let records = vec![
+            record("general", "alice", "2025-11-30 12:00:00"),
+            record("general", "bob", "2025-12-01 12:00:00"),
+            record("random", "alice", "2025-12-02 12:00:00"),
+        ];
+
+        writer.write(&records).unwrap();
+
+        let summary: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            summary["channel_ranges"],
+            json!({
+                "general": {"first": "2025-11-30 12:00:00", "last": "2025-12-01 12:00:00", "span_days": 1},
+                "random": {"first": "2025-12-02 12:00:00", "last": "2025-12-02 12:00:00", "span_days": 0}
+            })
+        );
+    }
+
+    #[test]
+    fn test_summary_json_is_reproducible_with_fixed_generated_at() {
+        let dir = TempDir::new().unwrap();
+        let generated_at = core::utils::resolve_generated_at(None, Some("1765881000")).unwrap();
+        let records = vec![record("general", "alice", "2025-12-01 12:00:00")];
+
+        let write = |name: &str| {
+            let path = dir.path().join(name);
+            SummaryJsonWriter::new(path.to_string_lossy().into_owned())
+                .with_generated_at(Some(generated_at.clone()))
+                .write(&records)
+                .unwrap();
+            fs::read(path).unwrap()
+        };
+        let (first, second) = (write("first.json"), write("second.json"));
+
+        assert_eq!(first, second);
+        let summary: Value = serde_json::from_slice(&first).unwrap();
+        assert_eq!(summary["generated_at"], "2025-12-16T10:30:00Z");
+    }
+}
@@ -0,0 +1,162 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Writes every record as one JSON array, readable back by `JsonDataRepository`
+pub struct JsonRecordsWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+    epoch_timestamps: bool,
+    compact: bool,
+}
+
+impl JsonRecordsWriter {
+    pub fn new(output_path: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new("")), output_path)
+    }
+
+    /// Creates a records writer emitting `output_path` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>, output_path: String) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(output_path),
+            epoch_timestamps: false,
+            compact: false,
+        }
+    }
+
+    /// Emits `timestamp` as integer Unix seconds parsed from the raw timestamp (null when
+    /// unparseable). Such files can no longer be read back by `JsonDataRepository`
+    pub fn with_epoch_timestamps(mut self, epoch_timestamps: bool) -> Self {
+        self.epoch_timestamps = epoch_timestamps;
+        self
+    }
+
+    /// Omits fields that are null or empty collections from each record
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Serializes a record, applying the compact and epoch-timestamp settings
+    fn record_value(&self, record: &ExtractedRecord) -> Result<Value> {
+        let mut value = serde_json::to_value(record)?;
+        let Some(object) = value.as_object_mut() else {
+            return Ok(value);
+        };
+
+        if self.compact {
+            object.retain(|_, field| !is_empty_field(field));
+        }
+        if self.epoch_timestamps {
+            object.insert("timestamp".to_string(), record.epoch_seconds().into());
+        }
+        Ok(value)
+    }
+}
+
+/// Whether a serialized field carries no information: null or an empty array/object
+fn is_empty_field(field: &Value) -> bool {
+    match field {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+impl MarkdownWriter for JsonRecordsWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let json = if self.epoch_timestamps || self.compact {
+            let values = records
+                .iter()
+                .map(|record| self.record_value(record))
+                .collect::<Result<Vec<_>>>()?;
+            serde_json::to_string_pretty(&values)?
+        } else {
+            serde_json::to_string_pretty(records)?
+        };
+        self.sink.write_file(&self.output_path, json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_epoch_timestamps_are_numeric() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("records.json");
+        let writer = JsonRecordsWriter::new(path.to_string_lossy().into_owned()).with_epoch_timestamps(true);
+        let records = vec![
+            ExtractedRecord {
+                timestamp: "2025-12-16 10:30:00 UTC".to_string(),
+                raw_timestamp: "2025-12-16T10:30:00Z".to_string(),
+                ..Default::default()
+            },
+            ExtractedRecord {
+                timestamp: "garbage".to_string(),
+                raw_timestamp: "garbage".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        writer.write(&records).unwrap();
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written[0]["timestamp"], Value::from(1_765_881_000));
+        assert_eq!(written[0]["raw_timestamp"], "2025-12-16T10:30:00Z");
+        assert_eq!(written[1]["timestamp"], Value::Null);
+    }
+
+    #[test]
+    fn test_compact_json_keeps_only_populated_keys() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("records.json");
+        let writer = JsonRecordsWriter::new(path.to_string_lossy().into_owned()).with_compact(true);
+        let record = ExtractedRecord {
+            channel_name: "general".to_string(),
+            username: "alice".to_string(),
+            timestamp: "2025-12-16 10:30:00 UTC".to_string(),
+            raw_timestamp: "2025-12-16T10:30:00Z".to_string(),
+            content: "hello".to_string(),
+            ..Default::default()
+        };
+
+        writer.write(&[record]).unwrap();
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut keys: Vec<&str> = written[0].as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec!["channel_name", "content", "deleted", "duplicate", "forwarded", "is_thread", "pinned", "raw_timestamp", "timestamp", "username"]
+        );
+    }
+
+    #[test]
+    fn test_records_stream_as_plain_json() {
+        let sink = Arc::new(core::sinks::StreamSink::new(Vec::new()));
+        let writer = JsonRecordsWriter::with_sink(sink.clone(), "-".to_string());
+        let record = ExtractedRecord {
+            channel_name: "general".to_string(),
+            content: "hello".to_string(),
+            ..Default::default()
+        };
+
+        writer.write(&[record]).unwrap();
+        drop(writer);
+
+        let output = Arc::try_unwrap(sink).ok().unwrap().into_inner().unwrap();
+        let written: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(written[0]["channel_name"], "general");
+        assert_eq!(written[0]["content"], "hello");
+    }
+}
@@ -0,0 +1,109 @@
+use core::domain::ExtractedRecord;
+use core::ports::{DataRepository, Result};
+use core::utils::{format_timestamp_in, OutputTimezone};
+use std::fs;
+
+/// Reads records from a JSON array, in the shape written by `JsonRecordsWriter`
+/// Missing fields take their defaults; timestamps are formatted like the SQLite adapter's,
+/// keeping the input string as the raw timestamp unless one is present
+pub struct JsonDataRepository {
+    json_path: String,
+    timezone: OutputTimezone,
+}
+
+impl JsonDataRepository {
+    /// Creates a new JsonDataRepository reading the given file
+    pub fn new(json_path: String) -> Self {
+        Self {
+            json_path,
+            timezone: OutputTimezone::default(),
+        }
+    }
+
+    /// Formats timestamps in this timezone instead of the local one
+    pub fn with_timezone(mut self, timezone: OutputTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+}
+
+impl DataRepository for JsonDataRepository {
+    fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+        let json = fs::read_to_string(&self.json_path)?;
+        let mut records: Vec<ExtractedRecord> = serde_json::from_str(&json)?;
+
+        for record in &mut records {
+            if record.raw_timestamp.is_empty() {
+                record.raw_timestamp = record.timestamp.clone();
+            }
+            record.timestamp = format_timestamp_in(&record.timestamp, self.timezone);
+        }
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonRecordsWriter;
+    use core::ports::MarkdownWriter;
+    use core::utils::format_timestamp_to_local;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip_through_records_writer() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("records.json").to_string_lossy().into_owned();
+        let records = vec![
+            ExtractedRecord {
+                channel_name: "general".to_string(),
+                channel_id: Some("1".to_string()),
+                username: "alice".to_string(),
+                user_id: Some("u1".to_string()),
+                timestamp: "2025-12-16T10:30:00Z".to_string(),
+                content: "hello\nworld".to_string(),
+                message_id: Some("m1".to_string()),
+                attachments: vec!["https://cdn.example.com/a.png".to_string()],
+                ..Default::default()
+            },
+            ExtractedRecord {
+                channel_name: "release-thread".to_string(),
+                username: "bob".to_string(),
+                timestamp: String::new(),
+                reply_to: Some("m1".to_string()),
+                deleted: true,
+                is_thread: true,
+                ..Default::default()
+            },
+        ];
+
+        JsonRecordsWriter::new(path.clone()).write(&records).unwrap();
+        let read_back = JsonDataRepository::new(path).fetch_all_records().unwrap();
+
+        let expected: Vec<ExtractedRecord> = records
+            .into_iter()
+            .map(|record| ExtractedRecord {
+                timestamp: format_timestamp_to_local(&record.timestamp),
+                raw_timestamp: record.timestamp.clone(),
+                ..record
+            })
+            .collect();
+        assert_eq!(read_back, expected);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("minimal.json");
+        fs::write(&path, r#"[{"channel_name": "general", "username": "alice", "content": "hi"}]"#).unwrap();
+
+        let records = JsonDataRepository::new(path.to_string_lossy().into_owned())
+            .fetch_all_records()
+            .unwrap();
+
+        assert_eq!(records[0].content, "hi");
+        assert_eq!(records[0].message_id, None);
+        assert!(records[0].attachments.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+/// Order of channel sections in the index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelSort {
+    /// Alphabetical by channel name
+    #[default]
+    Alpha,
+    /// Most messages first, ties broken by name
+    Activity,
+    /// By channel id (numeric ids compare numerically), ties broken by name
+    Id,
+}
+
+impl FromStr for ChannelSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "alpha" => Ok(Self::Alpha),
+            "activity" => Ok(Self::Activity),
+            "id" => Ok(Self::Id),
+            other => Err(format!("unknown channel sort '{}' (expected alpha, activity or id)", other)),
+        }
+    }
+}
+
+/// A file written for one channel, as listed in the index
+#[derive(Debug, Clone)]
+pub(crate) struct IndexFile {
+    pub label: String,
+    pub filename: String,
+    pub messages: usize,
+}
+
+/// A channel section of the index
+#[derive(Debug, Clone)]
+pub(crate) struct IndexChannel {
+    pub name: String,
+    pub id: Option<String>,
+    pub category: Option<String>,
+    pub files: Vec<IndexFile>,
+}
+
+impl IndexChannel {
+    fn messages(&self) -> usize {
+        self.files.iter().map(|file| file.messages).sum()
+    }
+}
+
+/// Sorts channel sections in place; channels without an id sort after those with one
+pub(crate) fn sort_channels(channels: &mut [IndexChannel], sort: ChannelSort) {
+    match sort {
+        ChannelSort::Alpha => channels.sort_by(|a, b| a.name.cmp(&b.name)),
+        ChannelSort::Activity => channels.sort_by_key(|channel| (Reverse(channel.messages()), channel.name.clone())),
+        ChannelSort::Id => channels.sort_by_key(|channel| {
+            let id = channel.id.clone();
+            let numeric = id.as_deref().and_then(|id| id.parse::<u64>().ok());
+            (id.is_none(), numeric.is_none(), numeric, id, channel.name.clone())
+        }),
+    }
+}
+
+/// Section title for channels without a category
+const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Formats the index of written files, one section per channel in the given order
+/// With `categories`, channels are nested under alphabetical category sections,
+/// keeping their order within each; uncategorized channels come last
+pub(crate) fn format_index(channels: &[IndexChannel], categories: bool) -> String {
+    let mut output = String::from("# Index\n\n");
+    output.push_str(&format!("*{} channels*\n\n", channels.len()));
+
+    if !categories {
+        for channel in channels {
+            push_channel(&mut output, channel, "##");
+        }
+        return output;
+    }
+
+    let mut names: Vec<Option<&str>> = channels.iter().map(|channel| channel.category.as_deref()).collect();
+    names.sort_by_key(|name| (name.is_none(), *name));
+    names.dedup();
+    for name in names {
+        output.push_str(&format!("## {}\n\n", name.unwrap_or(UNCATEGORIZED)));
+        for channel in channels.iter().filter(|channel| channel.category.as_deref() == name) {
+            push_channel(&mut output, channel, "###");
+        }
+    }
+
+    output
+}
+
+/// Appends one channel's section, with a heading of the given level
+fn push_channel(output: &mut String, channel: &IndexChannel, heading: &str) {
+    output.push_str(&format!("{} #{}\n\n", heading, channel.name));
+    for file in &channel.files {
+        output.push_str(&format!("- [{}]({}) ({} messages)\n", file.label, file.filename, file.messages));
+    }
+    output.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(name: &str, id: Option<&str>, messages: usize) -> IndexChannel {
+        IndexChannel {
+            name: name.to_string(),
+            id: id.map(str::to_string),
+            category: None,
+            files: vec![IndexFile {
+                label: "2025-12".to_string(),
+                filename: format!("{}-2025-12.md", name),
+                messages,
+            }],
+        }
+    }
+
+    fn sorted_names(sort: ChannelSort) -> Vec<String> {
+        let mut channels = vec![
+            channel("alpha", Some("10"), 1),
+            channel("busy", Some("9"), 5),
+            channel("calm", None, 1),
+        ];
+        sort_channels(&mut channels, sort);
+        channels.into_iter().map(|channel| channel.name).collect()
+    }
+
+    #[test]
+    fn test_sort_channels() {
+        assert_eq!(sorted_names(ChannelSort::Alpha), vec!["alpha", "busy", "calm"]);
+        assert_eq!(sorted_names(ChannelSort::Activity), vec!["busy", "alpha", "calm"]);
+        assert_eq!(sorted_names(ChannelSort::Id), vec!["busy", "alpha", "calm"]);
+    }
+
+    #[test]
+    fn test_format_index_nests_channels_under_categories() {
+        let in_category = |name: &str, category: Option<&str>| IndexChannel {
+            category: category.map(str::to_string),
+            ..channel(name, None, 1)
+        };
+        let channels = vec![
+            in_category("announcements", Some("Info")),
+            in_category("general", Some("Chat")),
+            in_category("misc", None),
+            in_category("rules", Some("Info")),
+        ];
+
+        let index = format_index(&channels, true);
+
+        let headings: Vec<&str> = index.lines().filter(|line| line.starts_with('#')).collect();
+        assert_eq!(
+            headings,
+            vec![
+                "# Index",
+                "## Chat",
+                "### #general",
+                "## Info",
+                "### #announcements",
+                "### #rules",
+                "## Uncategorized",
+                "### #misc",
+            ]
+        );
+        assert!(index.contains("### #misc\n\n- [2025-12](misc-2025-12.md) (1 messages)\n"));
+    }
+}
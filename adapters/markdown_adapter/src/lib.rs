@@ -1,51 +1,923 @@
 use core::domain::ExtractedRecord;
-use core::ports::{MarkdownWriter, Result};
-use core::utils::{extract_year_month, sanitize_filename};
-use std::collections::BTreeMap;
-use std::fs;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use core::utils::{
+    extract_date_in, extract_period_in, extract_weekday_in, humanize_ago, sanitize_filename, seconds_between,
+    timestamp_to_epoch, wrap_content, Granularity, OutputTimezone,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
+
+pub mod index;
+pub mod links;
+pub mod output_dir;
+pub mod reactions;
+pub mod roster;
+
+pub use index::ChannelSort;
+pub use links::LinksWriter;
+pub use output_dir::{check_output_file, prepare_output_folder, ExistingOutput};
+pub use reactions::ReactionsCsvWriter;
+pub use roster::RosterWriter;
+
+use index::{format_index, sort_channels, IndexChannel, IndexFile};
+
+/// Determines which output file each record is written to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Grouping {
+    /// One file per channel and month: `<channel>-<YYYY-MM>.md`
+    /// (or per day or year, following `MarkdownOptions::granularity`)
+    #[default]
+    ChannelMonth,
+    /// One file per weekday across all channels: `monday.md` ... `sunday.md`
+    Weekday,
+    /// One file per message: `<channel>/<YYYY-MM-DD>/<message-id>.md`
+    /// Records without a message id or a parseable date are skipped
+    Message,
+}
+
+/// Rendering options for the Markdown writer
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// How many ancestors of a reply to render as nested quotes (0 disables)
+    pub reply_depth: usize,
+    /// Grouping dimension used to split records into files
+    pub grouping: Grouping,
+    /// Soft-wrap message content at this many columns (code blocks are exempt)
+    pub wrap: Option<usize>,
+    /// Render consecutive messages from the same author under a single header
+    pub group_consecutive: bool,
+    /// Maximum gap in minutes between grouped messages (None groups regardless of time)
+    pub group_window: Option<u64>,
+    /// Append links to the previous and next month of the same channel to each file
+    pub nav_footer: bool,
+    /// Fail instead of writing more than this many files
+    pub max_files: Option<usize>,
+    /// Also write `index.md` linking every channel-month file
+    pub index: bool,
+    /// Order of channel sections in the index
+    pub channel_sort: ChannelSort,
+    /// Nest index channel sections under their category (uncategorized channels last)
+    pub categories: bool,
+    /// Show timestamps exactly as stored instead of localized (grouping is unaffected)
+    pub raw_timestamps: bool,
+    /// Name the source database file in each message header
+    pub show_source: bool,
+    /// Period covered by each channel file (month by default)
+    pub granularity: Granularity,
+    /// Timezone in which files are split into days, months and weekdays
+    pub timezone: OutputTimezone,
+    /// Roll channel and weekday files over into `.partN.md` files past this many bytes
+    /// Files are only split between messages
+    pub max_file_bytes: Option<usize>,
+    /// Prefix each message with an HTML anchor `<a id="msg-<id>"></a>` and link reply quotes
+    /// to the quoted message when it is in the same file
+    pub anchors: bool,
+    /// Render image attachments inline as `![name](url)` instead of as links
+    pub embed_images: bool,
+    /// Link each message to its original in the source client (`[↗](url)`) when its jump URL is known
+    pub jump_links: bool,
+    /// Render the first line of multi-line messages in bold as a title above the rest
+    pub first_line_as_title: bool,
+    /// Notice (e.g. license text) placed at the top of every file, above the heading
+    pub header: Option<String>,
+    /// Follow each header timestamp with how long before this time it was, e.g. `(3 months ago)`
+    pub relative_to: Option<String>,
+    /// Obsidian vault layout: `<channel>/<period>.md` files with `tags: [channel/<name>]` frontmatter,
+    /// `[[wikilinks]]` between files and `^block` ids instead of HTML anchors
+    pub obsidian: bool,
+    /// Start each day in a file with a `## YYYY-MM-DD` subheading (local date)
+    pub day_dividers: bool,
+    /// Write every record into this one file (relative to the sink) instead of one file per
+    /// group: a `# #channel` section per channel with a `## <period>` section per period
+    pub single_file: Option<String>,
+    /// Add new messages to channel-period files left by an earlier run instead of replacing them,
+    /// skipping messages already in the file and raising its `*N messages*` count
+    /// Grouped messages get a `<!-- sent <timestamp> -->` line so later runs can recognize them
+    pub append: bool,
+}
+
+/// File name of the index written alongside channel-month files
+const INDEX_FILENAME: &str = "index.md";
+
+/// A rendered file: its name and contents
+type RenderedFile = (String, String);
+
+/// Rule separating messages in a file
+const SEPARATOR: &str = "---\n\n";
+
+/// Weekday file stems in calendar order, used to order weekday groups
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Names the given 1-based part of a file, e.g. `general-2025-12.part2.md`; part 1 keeps the name
+fn part_filename(filename: &str, number: usize) -> String {
+    match (number, filename.strip_suffix(".md")) {
+        (1, _) => filename.to_string(),
+        (_, Some(stem)) => format!("{}.part{}.md", stem, number),
+        (_, None) => format!("{}.part{}", filename, number),
+    }
+}
+
+/// Image file extensions rendered inline by `embed_images`
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg"];
+
+/// Returns the path of an attachment URL without its query string or fragment
+fn attachment_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+/// Names an attachment after the last segment of its URL path, e.g. `photo.png`
+fn attachment_name(url: &str) -> &str {
+    let path = attachment_path(url).trim_end_matches('/');
+    path.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or(path)
+}
+
+/// Whether an attachment URL points to an image, judged by its file extension
+fn is_image_url(url: &str) -> bool {
+    attachment_name(url)
+        .rsplit_once('.')
+        .is_some_and(|(_, extension)| IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Assigns each message id in a file an HTML anchor id such as `msg-123`
+/// Characters outside `[A-Za-z0-9_-]` become `-`, and clashes get a `-2`, `-3`, ... suffix
+fn message_anchors<'a>(records: &[&'a ExtractedRecord]) -> HashMap<&'a str, String> {
+    let mut anchors: HashMap<&str, String> = HashMap::new();
+    let mut taken: HashSet<String> = HashSet::new();
+    for id in records.iter().filter_map(|record| record.message_id.as_deref()) {
+        if anchors.contains_key(id) {
+            continue;
+        }
+        let sanitized: String = id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+            .collect();
+        let base = format!("msg-{}", sanitized);
+        let mut anchor = base.clone();
+        let mut suffix = 1;
+        while !taken.insert(anchor.clone()) {
+            suffix += 1;
+            anchor = format!("{}-{}", base, suffix);
+        }
+        anchors.insert(id, anchor);
+    }
+    anchors
+}
+
+/// Links to another generated file, as `[label](target)` or, for Obsidian, as a `[[wikilink]]`
+/// Wikilinks name the target relative to the vault root, so they work from nested files
+fn file_link(label: &str, target: &str, wikilinks: bool) -> String {
+    if wikilinks {
+        let note = target.strip_suffix(".md").unwrap_or(target);
+        if label == target {
+            format!("[[{}]]", note)
+        } else {
+            format!("[[{}|{}]]", note, label)
+        }
+    } else {
+        format!("[{}]({})", label, target)
+    }
+}
+
+/// Obsidian tag naming a channel, e.g. `channel/dev-ops`; tags cannot contain whitespace
+pub fn channel_tag(channel_name: &str) -> String {
+    let name: String = sanitize_filename(channel_name)
+        .chars()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .collect();
+    format!("channel/{}", name)
+}
+
+/// Builds a blockquote marker for the given nesting depth, e.g. `> >` for 2
+fn quote_prefix(depth: usize) -> String {
+    vec![">"; depth].join(" ")
+}
+
+/// Formats links to the neighbouring files of a channel and the index, or nothing if there are none
+fn format_nav_footer(previous: Option<&str>, next: Option<&str>, up: Option<&str>, wikilinks: bool) -> String {
+    let links: Vec<String> = previous
+        .map(|name| file_link("← Previous", name, wikilinks))
+        .into_iter()
+        .chain(up.map(|name| file_link("↑ Index", name, wikilinks)))
+        .chain(next.map(|name| file_link("Next →", name, wikilinks)))
+        .collect();
+    if links.is_empty() {
+        return String::new();
+    }
+    format!("{}\n", links.join(" | "))
+}
+
+/// Starts the line recording the timestamp of a grouped message in appendable files, which
+/// would otherwise only show the timestamp of the first message of its group
+const SENT_MARKER: &str = "<!-- sent ";
+
+/// A message found in a file written by an earlier run: its author, its timestamp as shown in
+/// the file (without any relative time) and its text up to the next message
+struct WrittenMessage<'a> {
+    username: &'a str,
+    timestamp: &'a str,
+    text: &'a str,
+}
+
+/// Removes the `<a id="…"></a>` anchor that may precede a message
+fn strip_anchor(text: &str) -> &str {
+    match text.find("</a>") {
+        Some(end) if text.starts_with("<a id=") => &text[end + "</a>".len()..],
+        _ => text,
+    }
+}
+
+/// Parses a message header line (`**user** *timestamp*…`) into the author and timestamp
+fn parse_header(line: &str) -> Option<(&str, &str)> {
+    let (username, rest) = strip_anchor(line).strip_prefix("**")?.split_once("** *")?;
+    let timestamp = rest.split('*').next().unwrap_or_default();
+    Some((username, timestamp.split(" (").next().unwrap_or(timestamp)))
+}
+
+/// Finds the messages in a file written by an earlier run: one per header, plus one per
+/// `<!-- sent … -->` marker for the grouped messages that follow a header
+fn written_messages(existing: &str) -> Vec<WrittenMessage<'_>> {
+    let mut messages = Vec::new();
+    for block in existing.split(SEPARATOR) {
+        let mut lines = block.split_inclusive('\n');
+        let mut offset = 0;
+        let mut header = None;
+        for line in lines.by_ref() {
+            offset += line.len();
+            header = parse_header(line);
+            if header.is_some() {
+                break;
+            }
+        }
+        let Some((username, mut timestamp)) = header else {
+            continue;
+        };
+
+        let mut start = offset;
+        for line in lines {
+            let sent = line.trim_end().strip_prefix(SENT_MARKER).and_then(|line| line.strip_suffix(" -->"));
+            if let Some(sent) = sent {
+                messages.push(WrittenMessage {
+                    username,
+                    timestamp,
+                    text: &block[start..offset],
+                });
+                timestamp = sent;
+                start = offset + line.len();
+            }
+            offset += line.len();
+        }
+        messages.push(WrittenMessage {
+            username,
+            timestamp,
+            text: &block[start..],
+        });
+    }
+    messages
+}
+
+/// Whether the text of a message found in a file is exactly `content`, allowing only the anchor
+/// before it and the jump link and block id lines after it
+fn is_same_content(text: &str, content: &str) -> bool {
+    let Some(rest) = strip_anchor(text.trim_start()).strip_prefix(content.trim()) else {
+        return false;
+    };
+    // A longer message sharing the prefix goes on along the same line or with more text below
+    (rest.is_empty() || rest.starts_with('\n'))
+        && rest.lines().all(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with("[↗](") || line.starts_with("^msg-")
+        })
+}
 
 /// Markdown writer adapter implementation
 pub struct MarkdownWriterAdapter {
-    output_folder: String,
+    sink: Arc<dyn OutputSink>,
+    options: MarkdownOptions,
 }
 
 impl MarkdownWriterAdapter {
     pub fn new(output_folder: String) -> Self {
-        Self { output_folder }
+        Self::with_options(output_folder, MarkdownOptions::default())
+    }
+
+    /// Creates a writer emitting one combined file at `path` (see `MarkdownOptions::single_file`)
+    pub fn new_single_file(path: String) -> Self {
+        Self::with_options(
+            String::new(),
+            MarkdownOptions {
+                single_file: Some(path),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a writer with explicit rendering options
+    pub fn with_options(output_folder: String, options: MarkdownOptions) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new(output_folder)), options)
+    }
+
+    /// Creates a writer that emits its files into an arbitrary sink (e.g. an archive)
+    pub fn with_sink(sink: Arc<dyn OutputSink>, options: MarkdownOptions) -> Self {
+        Self { sink, options }
+    }
+
+    /// Walks the reply chain of a record, nearest ancestor first
+    /// Stops at `reply_depth` levels, at unknown ids, or when a cycle is detected
+    fn reply_ancestors<'a>(
+        &self,
+        record: &ExtractedRecord,
+        by_id: &HashMap<&str, &'a ExtractedRecord>,
+    ) -> Vec<&'a ExtractedRecord> {
+        let mut ancestors = Vec::new();
+        let mut visited: HashSet<&str> = record.message_id.as_deref().into_iter().collect();
+        let mut next = record.reply_to.as_deref();
+
+        while let Some(parent_id) = next {
+            if ancestors.len() >= self.options.reply_depth || !visited.insert(parent_id) {
+                break;
+            }
+            let Some(parent) = by_id.get(parent_id) else {
+                break;
+            };
+            ancestors.push(*parent);
+            next = parent.reply_to.as_deref();
+        }
+
+        ancestors
+    }
+
+    /// Returns the timestamp shown for a record: the raw stored value or the localized one,
+    /// followed by the relative time when `relative_to` is set and the timestamp parses
+    fn display_timestamp(&self, record: &ExtractedRecord) -> String {
+        let timestamp = self.shown_timestamp(record);
+        let relative = self.options.relative_to.as_deref().and_then(|now| seconds_between(&record.timestamp, now));
+        match relative {
+            Some(seconds) => format!("{} ({})", timestamp, humanize_ago(seconds)),
+            None => timestamp.to_string(),
+        }
+    }
+
+    /// Returns the raw stored timestamp or the localized one, as chosen by `raw_timestamps`
+    fn shown_timestamp<'a>(&self, record: &'a ExtractedRecord) -> &'a str {
+        if self.options.raw_timestamps {
+            &record.raw_timestamp
+        } else {
+            &record.timestamp
+        }
+    }
+
+    /// Formats a quoted message at the given blockquote nesting depth
+    /// `anchor` links the quote to the quoted message when it is in the same file
+    fn format_quote(&self, record: &ExtractedRecord, depth: usize, anchor: Option<&str>) -> String {
+        let prefix = quote_prefix(depth);
+        let mut output = format!("{} **{}** *{}*", prefix, record.username, self.display_timestamp(record));
+        if let Some(anchor) = anchor {
+            if self.options.obsidian {
+                output.push_str(&format!(" [[#^{}|↩]]", anchor));
+            } else {
+                output.push_str(&format!(" [↩](#{})", anchor));
+            }
+        }
+        output.push('\n');
+
+        for line in record.content.trim().lines() {
+            if line.trim().is_empty() {
+                output.push_str(&format!("{}\n", prefix));
+            } else {
+                output.push_str(&format!("{} {}\n", prefix, line));
+            }
+        }
+
+        // A quote line one level shallower closes this level before the next one
+        output.push_str(&format!("{}\n", quote_prefix(depth - 1)));
+        output
+    }
+
+    /// Formats the body of a message, followed by a blank line
+    fn format_content(&self, record: &ExtractedRecord) -> String {
+        let attachments = self.format_attachments(record);
+        if record.deleted {
+            "*[deleted]*\n\n".to_string()
+        } else if !record.content.trim().is_empty() {
+            let mut content = record.content.trim();
+            let mut title = String::new();
+            if self.options.first_line_as_title {
+                if let Some((first, rest)) = content.split_once('\n') {
+                    title = format!("**{}**\n\n", first.trim());
+                    content = rest.trim();
+                }
+            }
+            match self.options.wrap {
+                Some(width) => format!("{}{}\n\n{}", title, wrap_content(content, width), attachments),
+                None => format!("{}{}\n\n{}", title, content, attachments),
+            }
+        } else if !attachments.is_empty() {
+            attachments
+        } else {
+            "*[No content]*\n\n".to_string()
+        }
+    }
+
+    /// Formats a record's attachment URLs as links, one per line, or as inline images with
+    /// `embed_images`; markers that are not URLs (such as the crawler's `Yes`) are skipped
+    fn format_attachments(&self, record: &ExtractedRecord) -> String {
+        let lines: Vec<String> = record
+            .attachments
+            .iter()
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+            .map(|url| {
+                let name = attachment_name(url);
+                if self.options.embed_images && is_image_url(url) {
+                    format!("![{}]({})", name, url)
+                } else {
+                    format!("[{}]({})", name, url)
+                }
+            })
+            .collect();
+        if lines.is_empty() {
+            return String::new();
+        }
+        format!("{}\n\n", lines.join("\n"))
+    }
+
+    /// Whether `record` can be rendered under the header of the preceding `previous`
+    /// Requires the same author and channel, and a gap within `group_window` when set;
+    /// a gap that cannot be computed splits the group. Forwards always get their own header
+    fn continues_group(&self, previous: &ExtractedRecord, record: &ExtractedRecord) -> bool {
+        if !self.options.group_consecutive
+            || record.forwarded
+            || previous.username != record.username
+            || previous.user_id != record.user_id
+            || previous.channel_name != record.channel_name
+        {
+            return false;
+        }
+
+        match self.options.group_window {
+            Some(minutes) => seconds_between(&previous.timestamp, &record.timestamp)
+                .is_some_and(|gap| (0..=minutes as i64 * 60).contains(&gap)),
+            None => true,
+        }
     }
 
-    /// Formats records into markdown for a single channel-month group
-    fn format_markdown(&self, channel_name: &str, records: &[&ExtractedRecord]) -> String {
+    /// Formats records into markdown for a single file group
+    /// `title` is rendered as the top-level heading, e.g. `#general` or `Monday`
+    fn format_markdown(
+        &self,
+        title: &str,
+        records: &[&ExtractedRecord],
+        by_id: &HashMap<&str, &ExtractedRecord>,
+    ) -> String {
         if records.is_empty() {
             return String::new();
         }
+        self.format_part(1, title, &self.format_blocks(records, by_id, None), None, None)
+    }
+
+    /// Formats records into one or more files named after `filename`, each ending with `footer`,
+    /// splitting between messages once a written file would grow past `max_file_bytes`; parts
+    /// after the first are `<stem>.partN.md`
+    fn format_parts(
+        &self,
+        filename: &str,
+        title: &str,
+        channel_name: Option<&str>,
+        footer: &str,
+        records: &[&ExtractedRecord],
+        by_id: &HashMap<&str, &ExtractedRecord>,
+    ) -> Vec<RenderedFile> {
+        let Some(limit) = self.options.max_file_bytes else {
+            let markdown_content = self.format_markdown(title, records, by_id) + footer;
+            return vec![(filename.to_string(), markdown_content)];
+        };
+
+        // Reserve room for everything around the messages, sized for the longest part number and
+        // links any part could get, then pack whole message blocks greedily; a single oversized
+        // block still gets its own part
+        let most = records.len();
+        let longest_name = part_filename(filename, most);
+        let longest_title = format!("{} (part {} of {})", title, most, most);
+        let frame = self.format_part(1, &longest_title, &[(most, String::new())], Some(&longest_name), Some(&longest_name));
+        let overhead = self.frontmatter(channel_name).len() + self.format_header().len() + frame.len()
+            - SEPARATOR.len()
+            + footer.len();
+        let mut parts: Vec<Vec<(usize, String)>> = Vec::new();
+        let mut size = 0;
+        for block in self.format_blocks(records, by_id, None) {
+            let block_size = block.1.len() + SEPARATOR.len();
+            if parts.is_empty() || size + block_size > limit {
+                parts.push(Vec::new());
+                size = overhead;
+            }
+            size += block_size;
+            if let Some(part) = parts.last_mut() {
+                part.push(block);
+            }
+        }
+
+        let count = parts.len();
+        let names: Vec<String> = (1..=count).map(|number| part_filename(filename, number)).collect();
+        parts
+            .iter()
+            .enumerate()
+            .map(|(index, blocks)| {
+                let part_title = match count {
+                    1 => title.to_string(),
+                    _ => format!("{} (part {} of {})", title, index + 1, count),
+                };
+                let previous = index.checked_sub(1).map(|i| names[i].as_str());
+                let next = names.get(index + 1).map(String::as_str);
+                let markdown_content = self.format_part(1, &part_title, blocks, previous, next) + footer;
+                (names[index].clone(), markdown_content)
+            })
+            .collect()
+    }
 
+    /// Renders a file (or a section of one, under a deeper heading `level`) from message blocks,
+    /// with links to the parts before and after it if any
+    fn format_part(
+        &self,
+        level: usize,
+        title: &str,
+        blocks: &[(usize, String)],
+        previous: Option<&str>,
+        next: Option<&str>,
+    ) -> String {
+        let messages: usize = blocks.iter().map(|(count, _)| count).sum();
         let mut output = String::new();
-        output.push_str(&format!("# #{}\n\n", channel_name));
-        output.push_str(&format!("*{} messages*\n\n", records.len()));
-        output.push_str("---\n\n");
+        output.push_str(&format!("{} {}\n\n", "#".repeat(level), title));
+        output.push_str(&format!("*{} messages*\n\n", messages));
+        if let Some(previous) = previous {
+            output.push_str(&format!("*Continued from {}*\n\n", file_link(previous, previous, self.options.obsidian)));
+        }
+        output.push_str(SEPARATOR);
+        for (_, block) in blocks {
+            output.push_str(block);
+            output.push_str(SEPARATOR);
+        }
+        if let Some(next) = next {
+            output.push_str(&format!("*Continued in {}*\n\n", file_link(next, next, self.options.obsidian)));
+        }
+        output
+    }
 
-        // Format each message
+    /// Formats each message under its own header, with grouped messages folded into the
+    /// block of the message they continue; returns each block with its message count.
+    /// `previous_date` is the day divider already written above these messages, if any
+    fn format_blocks(
+        &self,
+        records: &[&ExtractedRecord],
+        by_id: &HashMap<&str, &ExtractedRecord>,
+        mut previous_date: Option<String>,
+    ) -> Vec<(usize, String)> {
+        let anchors = if self.options.anchors { message_anchors(records) } else { HashMap::new() };
+        let anchor = |record: &ExtractedRecord| record.message_id.as_deref().and_then(|id| anchors.get(id));
+        // Obsidian references blocks by a trailing `^id` rather than an HTML anchor before them
+        let anchor_tag = |record: &ExtractedRecord| match anchor(record) {
+            Some(anchor) if !self.options.obsidian => format!("<a id=\"{}\"></a>", anchor),
+            _ => String::new(),
+        };
+        let block_id = |record: &ExtractedRecord| match anchor(record) {
+            Some(anchor) if self.options.obsidian => format!(" ^{}", anchor),
+            _ => String::new(),
+        };
+        let mut blocks: Vec<(usize, String)> = Vec::new();
+        let mut previous: Option<&ExtractedRecord> = None;
         for record in records {
-            // Format message header with username and timestamp
-            output.push_str(&format!(
-                "**{}** *{}*\n\n",
-                record.username, record.timestamp
-            ));
-            
-            // Format message content
-            if !record.content.trim().is_empty() {
-                let content = record.content.trim();
-                output.push_str(&format!("{}\n\n", content));
-            } else {
-                output.push_str("*[No content]*\n\n");
+            // A new day gets a subheading, and breaks any group so the subheading sits between messages
+            let date = extract_date_in(&record.timestamp, self.options.timezone).filter(|_| self.options.day_dividers);
+            let new_day = date.is_some() && date != previous_date;
+            if new_day {
+                previous_date = date.clone();
+            }
+
+            // Quote the reply chain, outermost ancestor first
+            let ancestors = self.reply_ancestors(record, by_id);
+            let continues_group = !new_day
+                && ancestors.is_empty()
+                && previous.is_some_and(|prev| self.continues_group(prev, record));
+            previous = Some(record);
+
+            // A grouped message shares the header of the one before it
+            if let Some((count, output)) = blocks.last_mut().filter(|_| continues_group) {
+                // Appendable files record each grouped message's own timestamp so it can be matched
+                if self.options.append {
+                    output.push_str(&format!("{}{} -->\n", SENT_MARKER, self.shown_timestamp(record)));
+                }
+                output.push_str(&anchor_tag(record));
+                output.push_str(&self.format_content(record));
+                let jump_link = self.jump_link(record);
+                if !jump_link.is_empty() {
+                    output.push_str(&format!("{}\n\n", jump_link.trim_start()));
+                }
+                let block_id = block_id(record);
+                if !block_id.is_empty() {
+                    output.push_str(&format!("{}\n\n", block_id.trim_start()));
+                }
+                *count += 1;
+                continue;
+            }
+            let mut output = String::new();
+            if let Some(date) = date.filter(|_| new_day) {
+                output.push_str(&format!("## {}\n\n", date));
+            }
+
+            for (index, ancestor) in ancestors.iter().enumerate().rev() {
+                let anchor = ancestor.message_id.as_deref().and_then(|id| anchors.get(id));
+                output.push_str(&self.format_quote(ancestor, index + 1, anchor.map(String::as_str)));
+            }
+
+            // Format message header with username and timestamp, plus the message number if set
+            // Weekday files mix channels, so the header names the channel too
+            output.push_str(&anchor_tag(record));
+            output.push_str(&format!("**{}** *{}*", record.username, self.display_timestamp(record)));
+            if let Some(number) = record.channel_index {
+                output.push_str(&format!(" #{}", number));
             }
-            
-            output.push_str("---\n\n");
+            if self.options.grouping == Grouping::Weekday {
+                output.push_str(&format!(" in #{}", record.channel_name));
+            }
+            if let Some(source) = record.source.as_deref().filter(|_| self.options.show_source) {
+                let file_name = Path::new(source).file_name().map_or(source.into(), |name| name.to_string_lossy());
+                output.push_str(&format!(" from `{}`", file_name));
+            }
+            if record.forwarded {
+                output.push_str(" ↪ forwarded");
+            }
+            if record.duplicate {
+                output.push_str(" ⚠ duplicate");
+            }
+            if let Some(language) = &record.language {
+                output.push_str(&format!(" `{}`", language));
+            }
+            output.push_str(&self.jump_link(record));
+            output.push_str(&block_id(record));
+            output.push_str("\n\n");
+
+            output.push_str(&self.format_content(record));
+            blocks.push((1, output));
         }
 
-        output
+        blocks
+    }
+
+    /// Whether a record is already among the messages of a file written by an earlier run, matched
+    /// exactly by author, shown timestamp and content
+    fn is_written(&self, written: &[WrittenMessage], record: &ExtractedRecord) -> bool {
+        let timestamp = self.shown_timestamp(record);
+        let content = self.format_content(record);
+        written.iter().any(|message| {
+            message.username == record.username
+                && message.timestamp == timestamp
+                && is_same_content(message.text, &content)
+        })
+    }
+
+    /// Adds the records not already in `existing`, a file written by an earlier run, after its last
+    /// message and raises its `*N messages*` count; returns how many were added and the new contents
+    fn append_to(
+        &self,
+        filename: &str,
+        existing: &str,
+        records: &[&ExtractedRecord],
+        by_id: &HashMap<&str, &ExtractedRecord>,
+    ) -> Result<(usize, String)> {
+        let written = written_messages(existing);
+        let new_records: Vec<&ExtractedRecord> =
+            records.iter().copied().filter(|record| !self.is_written(&written, record)).collect();
+        if new_records.is_empty() {
+            return Ok((0, existing.to_string()));
+        }
+
+        // The count line sits below the heading, before the first message
+        let mut count_line = None;
+        let mut offset = 0;
+        for line in existing.split_inclusive('\n') {
+            let text = line.trim_end();
+            let count = text
+                .strip_prefix('*')
+                .and_then(|text| text.strip_suffix(" messages*"))
+                .and_then(|count| count.parse::<usize>().ok());
+            if let Some(count) = count {
+                count_line = Some((offset, offset + text.len(), count));
+                break;
+            }
+            offset += line.len();
+        }
+        let (count_start, count_end, count) =
+            count_line.ok_or_else(|| format!("cannot append to {}: no `*N messages*` line", filename))?;
+        // New messages go after the last separator, ahead of any footer
+        let insert_at = existing
+            .rfind(SEPARATOR)
+            .map(|index| index + SEPARATOR.len())
+            .filter(|&index| index > count_end)
+            .ok_or_else(|| format!("cannot append to {}: no message separator", filename))?;
+
+        let mut output = String::new();
+        output.push_str(&existing[..count_start]);
+        output.push_str(&format!("*{} messages*", count + new_records.len()));
+        output.push_str(&existing[count_end..insert_at]);
+        // Messages continuing the file's last day go under its existing divider
+        let last_date = existing.lines().rev().find_map(|line| line.strip_prefix("## ")).map(str::to_string);
+        for (_, block) in self.format_blocks(&new_records, by_id, last_date) {
+            output.push_str(&block);
+            output.push_str(SEPARATOR);
+        }
+        output.push_str(&existing[insert_at..]);
+        Ok((new_records.len(), output))
+    }
+
+    /// Formats the link to a record's original message, with a leading space, or nothing
+    fn jump_link(&self, record: &ExtractedRecord) -> String {
+        match record.jump_url.as_deref().filter(|_| self.options.jump_links) {
+            Some(url) => format!(" [↗]({})", url),
+            None => String::new(),
+        }
+    }
+
+    /// Formats the configured header notice followed by a blank line, or nothing
+    fn format_header(&self) -> String {
+        match self.options.header.as_deref().map(str::trim_end) {
+            Some(header) if !header.is_empty() => format!("{}\n\n", header),
+            _ => String::new(),
+        }
+    }
+
+    /// Writes a file through the sink with the header notice prepended, preceded for Obsidian by
+    /// frontmatter tagging the file's channel when it has a single one
+    fn write_file(&self, path: &Path, channel_name: Option<&str>, content: &str) -> Result<()> {
+        let file = format!("{}{}{}", self.frontmatter(channel_name), self.format_header(), content);
+        self.sink.write_file(path, file.as_bytes())
+    }
+
+    /// Formats the Obsidian frontmatter tagging a file's channel, or nothing
+    fn frontmatter(&self, channel_name: Option<&str>) -> String {
+        match channel_name.filter(|_| self.options.obsidian) {
+            Some(channel_name) => format!("---\ntags: [{}]\n---\n\n", channel_tag(channel_name)),
+            None => String::new(),
+        }
+    }
+
+    /// Errors if writing `count` files would exceed the configured `max_files`
+    fn check_file_count(&self, count: usize) -> Result<()> {
+        match self.options.max_files {
+            Some(limit) if count > limit => {
+                Err(format!("export would write {} files, above the --max-files limit of {}", count, limit).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Groups records by channel and period, sorted so each channel's periods are adjacent and in order
+    /// Records within a group are ordered by the same parsed time that picked their period
+    fn group_by_channel_period<'a>(
+        &self,
+        records: &'a [ExtractedRecord],
+    ) -> BTreeMap<(String, String), Vec<&'a ExtractedRecord>> {
+        let mut grouped: BTreeMap<(String, String), Vec<&ExtractedRecord>> = BTreeMap::new();
+        for record in records {
+            // Extract the period from timestamp, default to "unknown" if parsing fails
+            let period = extract_period_in(&record.timestamp, self.options.granularity, self.options.timezone)
+                .unwrap_or_else(|| "unknown".to_string());
+            grouped.entry((record.channel_name.clone(), period)).or_default().push(record);
+        }
+        grouped.values_mut().for_each(|records| sort_chronologically(records));
+        grouped
+    }
+
+    /// Writes every record into one file, with the channel-period groups of the per-file layout
+    /// as `## <period>` sections below a `# #channel` heading per channel
+    fn write_single_file(
+        &self,
+        path: &str,
+        records: &[ExtractedRecord],
+        by_id: &HashMap<&str, &ExtractedRecord>,
+    ) -> Result<()> {
+        let mut output = String::new();
+        let mut current_channel: Option<&String> = None;
+        for ((channel_name, period), channel_records) in &self.group_by_channel_period(records) {
+            if current_channel != Some(channel_name) {
+                output.push_str(&format!("# #{}\n\n", channel_name));
+                current_channel = Some(channel_name);
+            }
+            output.push_str(&self.format_part(2, period, &self.format_blocks(channel_records, by_id, None), None, None));
+        }
+        self.write_file(Path::new(path), None, &output)?;
+        log::debug!(file = path, count = records.len(); "wrote markdown file");
+        Ok(())
+    }
+
+    /// Writes each record to its own minimal file below a channel and date directory
+    fn write_per_message(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let mut files = Vec::new();
+        for record in records {
+            let (Some(message_id), Some(date)) = (record.message_id.as_deref(), extract_date_in(&record.timestamp, self.options.timezone))
+            else {
+                log::warn!(channel = record.channel_name.as_str(); "skipping message without id or date");
+                continue;
+            };
+            let path = Path::new(&sanitize_filename(&record.channel_name))
+                .join(date)
+                .join(format!("{}.md", sanitize_filename(message_id)));
+            files.push((path, record));
+        }
+        self.check_file_count(files.len())?;
+
+        for (path, record) in files {
+            let content = format!(
+                "**{}** *{}*{}\n\n{}",
+                record.username,
+                self.display_timestamp(record),
+                self.jump_link(record),
+                self.format_content(record).trim_end()
+            );
+            self.write_file(&path, Some(&record.channel_name), &format!("{}\n", content))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `index.md` listing each channel's files in the configured channel order
+    /// `files` holds the filename of each `grouped` entry, in the same order
+    fn write_index(
+        &self,
+        grouped: &BTreeMap<(String, String), Vec<&ExtractedRecord>>,
+        files: &[(&String, String)],
+    ) -> Result<()> {
+        let mut channels: Vec<IndexChannel> = Vec::new();
+        for (((channel_name, year_month), channel_records), (_, filename)) in grouped.iter().zip(files) {
+            if channels.last().is_none_or(|channel| channel.name != *channel_name) {
+                channels.push(IndexChannel {
+                    name: channel_name.clone(),
+                    id: channel_records.iter().find_map(|record| record.channel_id.clone()),
+                    category: channel_records.iter().find_map(|record| record.category.clone()),
+                    files: Vec::new(),
+                });
+            }
+            if let Some(channel) = channels.last_mut() {
+                channel.files.push(IndexFile {
+                    label: year_month.clone(),
+                    filename: filename.clone(),
+                    messages: channel_records.len(),
+                });
+            }
+        }
+
+        sort_channels(&mut channels, self.options.channel_sort);
+        self.write_file(Path::new(INDEX_FILENAME), None, &format_index(&channels, self.options.categories))
+    }
+
+    /// Writes one file per weekday, each holding that weekday's records from every channel
+    fn write_by_weekday(
+        &self,
+        records: &[ExtractedRecord],
+        by_id: &HashMap<&str, &ExtractedRecord>,
+    ) -> Result<()> {
+        // Key by calendar position so files are produced Monday first, unknown last
+        let mut grouped: BTreeMap<usize, (String, Vec<&ExtractedRecord>)> = BTreeMap::new();
+
+        for record in records {
+            let weekday = extract_weekday_in(&record.timestamp, self.options.timezone).unwrap_or_else(|| "unknown".to_string());
+            let position = WEEKDAYS
+                .iter()
+                .position(|day| *day == weekday)
+                .unwrap_or(WEEKDAYS.len());
+            grouped
+                .entry(position)
+                .or_insert_with(|| (weekday, Vec::new()))
+                .1
+                .push(record);
+        }
+        grouped.values_mut().for_each(|(_, records)| sort_chronologically(records));
+        let rendered: Vec<(usize, Vec<RenderedFile>)> = grouped
+            .values()
+            .map(|(weekday, weekday_records)| {
+                let mut title = weekday.clone();
+                title[..1].make_ascii_uppercase();
+                let parts = self.format_parts(&format!("{}.md", weekday), &title, None, "", weekday_records, by_id);
+                (weekday_records.len(), parts)
+            })
+            .collect();
+        self.check_file_count(rendered.iter().map(|(_, parts)| parts.len()).sum())?;
+
+        for (count, parts) in rendered {
+            for (filename, markdown_content) in parts {
+                self.write_file(Path::new(&filename), None, &markdown_content)?;
+                log::debug!(file = filename.as_str(), count = count; "wrote markdown file");
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -55,33 +927,1037 @@ impl MarkdownWriter for MarkdownWriterAdapter {
             return Ok(());
         }
 
-        // Create output directory if it doesn't exist
-        let output_dir = Path::new(&self.output_folder);
-        fs::create_dir_all(output_dir)?;
+        // Index records by message id so replies can quote their ancestors
+        let by_id: HashMap<&str, &ExtractedRecord> = records
+            .iter()
+            .filter_map(|record| record.message_id.as_deref().map(|id| (id, record)))
+            .collect();
 
-        // Group records by channel and month: (channel_name, year_month) -> Vec<records>
-        let mut grouped: BTreeMap<(String, String), Vec<&ExtractedRecord>> = BTreeMap::new();
-        
-        for record in records {
-            // Extract year-month from timestamp, default to "unknown" if parsing fails
-            let year_month = extract_year_month(&record.timestamp)
-                .unwrap_or_else(|| "unknown".to_string());
-            
-            let key = (record.channel_name.clone(), year_month);
-            grouped.entry(key).or_insert_with(Vec::new).push(record);
+        if let Some(path) = &self.options.single_file {
+            return self.write_single_file(path, records, &by_id);
+        }
+        match self.options.grouping {
+            Grouping::Weekday => return self.write_by_weekday(records, &by_id),
+            Grouping::Message => return self.write_per_message(records),
+            Grouping::ChannelMonth => {}
+        }
+
+        let grouped = self.group_by_channel_period(records);
+
+        // Keys are sorted, so each channel's periods are adjacent and in order
+        let files: Vec<(&String, String)> = grouped
+            .keys()
+            .map(|(channel_name, year_month)| {
+                let filename = if self.options.obsidian {
+                    format!("{}/{}.md", sanitize_filename(channel_name), year_month)
+                } else {
+                    format!("{}-{}.md", sanitize_filename(channel_name), year_month)
+                };
+                (channel_name, filename)
+            })
+            .collect();
+
+        // Render a separate file (or parts of one) for each channel-month combination
+        let mut rendered: Vec<(&String, usize, Vec<RenderedFile>)> = Vec::new();
+        // Files left by an earlier run that get new messages added, with how many
+        let mut appended: Vec<(&String, usize, RenderedFile)> = Vec::new();
+        for (index, ((channel_name, _), channel_records)) in grouped.iter().enumerate() {
+            let filename = &files[index].1;
+
+            if let Some(existing) = self.options.append.then(|| self.sink.read_file(Path::new(filename))) {
+                if let Some(existing) = existing? {
+                    let existing = String::from_utf8(existing)
+                        .map_err(|_| format!("cannot append to {}: not valid UTF-8", filename))?;
+                    let (count, content) = self.append_to(filename, &existing, channel_records, &by_id)?;
+                    if count > 0 {
+                        appended.push((channel_name, count, (filename.clone(), content)));
+                    }
+                    continue;
+                }
+            }
+
+            let footer = if self.options.nav_footer {
+                let sibling = |i: usize| {
+                    files
+                        .get(i)
+                        .filter(|(channel, _)| *channel == channel_name)
+                        .map(|(_, name)| name.as_str())
+                };
+                let previous = index.checked_sub(1).and_then(sibling);
+                let up = self.options.index.then_some(INDEX_FILENAME);
+                format_nav_footer(previous, sibling(index + 1), up, self.options.obsidian)
+            } else {
+                String::new()
+            };
+            let title = format!("#{}", channel_name);
+            let parts = self.format_parts(filename, &title, Some(channel_name), &footer, channel_records, &by_id);
+            rendered.push((channel_name, channel_records.len(), parts));
+        }
+        self.check_file_count(rendered.iter().map(|(_, _, parts)| parts.len()).sum())?;
+
+        for (channel_name, count, parts) in rendered {
+            for (filename, markdown_content) in parts {
+                self.write_file(Path::new(&filename), Some(channel_name), &markdown_content)?;
+                log::debug!(
+                    file = filename.as_str(),
+                    channel = channel_name.as_str(),
+                    count = count;
+                    "wrote markdown file"
+                );
+            }
         }
 
-        // Write a separate file for each channel-month combination
-        for ((channel_name, year_month), channel_records) in grouped.iter() {
-            let sanitized_channel = sanitize_filename(channel_name);
-            let filename = format!("{}-{}.md", sanitized_channel, year_month);
-            let file_path = output_dir.join(&filename);
+        // Appended files already carry their frontmatter and header notice
+        for (channel_name, count, (filename, content)) in appended {
+            self.sink.write_file(Path::new(&filename), content.as_bytes())?;
+            log::debug!(
+                file = filename.as_str(),
+                channel = channel_name.as_str(),
+                count = count;
+                "appended to markdown file"
+            );
+        }
 
-            let markdown_content = self.format_markdown(channel_name, channel_records);
-            fs::write(&file_path, markdown_content)?;
+        if self.options.index {
+            self.write_index(&grouped, &files)?;
         }
 
         Ok(())
     }
 }
 
+/// Orders a file's records by their parsed time instead of the source order, which sorts the
+/// stored strings and so misorders timestamps written with different offsets
+/// The sort is stable, so records with equal or unparseable times keep their source order
+fn sort_chronologically(records: &mut [&ExtractedRecord]) {
+    records.sort_by_cached_key(|record| timestamp_to_epoch(&record.timestamp));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::application::{ExtractionOptions, ExtractionServiceImpl};
+    use core::ports::DataRepository;
+    use core::sinks::{MeasuringSink, PrefixedSink};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(id: &str, username: &str, content: &str, reply_to: Option<&str>) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: "general".to_string(),
+            username: username.to_string(),
+            user_id: None,
+            timestamp: "2025-12-16 10:30:00 UTC".to_string(),
+            content: content.to_string(),
+            message_id: Some(id.to_string()),
+            reply_to: reply_to.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn render(writer: &MarkdownWriterAdapter, records: &[ExtractedRecord]) -> String {
+        let by_id = records
+            .iter()
+            .filter_map(|r| r.message_id.as_deref().map(|id| (id, r)))
+            .collect();
+        let refs: Vec<&ExtractedRecord> = records.iter().collect();
+        writer.format_markdown("#general", &refs, &by_id)
+    }
+
+    /// Serves a fixed set of records to the extraction service
+    struct Records(Vec<ExtractedRecord>);
+
+    impl DataRepository for Records {
+        fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn reply_writer(reply_depth: usize) -> MarkdownWriterAdapter {
+        MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                reply_depth,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_reply_chain_renders_nested_quotes() {
+        let records = vec![
+            record("m1", "alice", "root", None),
+            record("m2", "bob", "reply", Some("m1")),
+            record("m3", "carol", "reply to reply", Some("m2")),
+        ];
+
+        let output = render(&reply_writer(2), &records);
+
+        let expected = "> > **alice** *2025-12-16 10:30:00 UTC*\n\
+                        > > root\n\
+                        >\n\
+                        > **bob** *2025-12-16 10:30:00 UTC*\n\
+                        > reply\n\
+                        \n\
+                        **carol** *2025-12-16 10:30:00 UTC*\n\n\
+                        reply to reply\n\n";
+        assert!(output.contains(expected));
+    }
+
+    #[test]
+    fn test_anchors_link_replies_to_messages_in_same_file() {
+        let writer = MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                reply_depth: 1,
+                anchors: true,
+                ..Default::default()
+            },
+        );
+        let records = vec![
+            record("m1", "alice", "root", None),
+            record("m2", "bob", "reply", Some("m1")),
+            record("a/b", "carol", "odd id", None),
+            record("a?b", "carol", "clashing id", None),
+        ];
+
+        let output = render(&writer, &records);
+
+        assert!(output.contains("<a id=\"msg-m1\"></a>**alice** *2025-12-16 10:30:00 UTC*\n\nroot"));
+        assert!(output.contains(
+            "> **alice** *2025-12-16 10:30:00 UTC* [↩](#msg-m1)\n> root\n\n<a id=\"msg-m2\"></a>**bob**"
+        ));
+        assert!(output.contains("<a id=\"msg-a-b\"></a>**carol**"));
+        assert!(output.contains("<a id=\"msg-a-b-2\"></a>**carol**"));
+        assert!(!render(&reply_writer(1), &records).contains("<a id="));
+    }
+
+    #[test]
+    fn test_reply_depth_limits_quoted_ancestors() {
+        let records = vec![
+            record("m1", "alice", "root", None),
+            record("m2", "bob", "reply", Some("m1")),
+            record("m3", "carol", "reply to reply", Some("m2")),
+        ];
+
+        let output = render(&reply_writer(1), &records);
+
+        assert!(!output.contains("> >"));
+        assert!(output.contains("> **bob**"));
+    }
+
+    #[test]
+    fn test_reply_depth_zero_renders_no_quotes() {
+        let records = vec![
+            record("m1", "alice", "root", None),
+            record("m2", "bob", "reply", Some("m1")),
+        ];
+
+        let output = render(&reply_writer(0), &records);
+
+        assert!(!output.contains('>'));
+    }
+
+    #[test]
+    fn test_reply_cycle_terminates() {
+        let records = vec![
+            record("m1", "alice", "first", Some("m2")),
+            record("m2", "bob", "second", Some("m1")),
+        ];
+
+        let output = render(&reply_writer(10), &records);
+
+        // Each message quotes only its partner once before the cycle is detected
+        assert_eq!(output.matches("> **bob**").count(), 1);
+        assert_eq!(output.matches("> **alice**").count(), 1);
+    }
+
+    #[test]
+    fn test_by_weekday_writes_one_file_per_weekday() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                grouping: Grouping::Weekday,
+                ..Default::default()
+            },
+        );
+        let mut monday = record("m1", "alice", "monday in general", None);
+        monday.timestamp = "2025-12-15 12:00:00".to_string();
+        let mut next_monday = record("m2", "bob", "monday in random", None);
+        next_monday.channel_name = "random".to_string();
+        next_monday.timestamp = "2025-12-22 12:00:00".to_string();
+        let mut saturday = record("m3", "carol", "saturday post", None);
+        saturday.timestamp = "2025-12-20 12:00:00".to_string();
+
+        writer.write(&[monday, saturday, next_monday]).unwrap();
+
+        let mut files: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["monday.md", "saturday.md"]);
+
+        let monday_content = fs::read_to_string(dir.path().join("monday.md")).unwrap();
+        assert!(monday_content.starts_with("# Monday\n\n*2 messages*"));
+        assert!(monday_content.contains("monday in general"));
+        assert!(monday_content.contains("in #random"));
+        let saturday_content = fs::read_to_string(dir.path().join("saturday.md")).unwrap();
+        assert!(saturday_content.contains("saturday post"));
+        assert!(!saturday_content.contains("monday in"));
+    }
+
+    #[test]
+    fn test_append_adds_only_new_messages_to_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                append: true,
+                ..Default::default()
+            },
+        );
+        let message = |id: &str, username: &str, content: &str, minute: u32| {
+            let mut record = record(id, username, content, None);
+            record.timestamp = format!("2025-12-16 10:{:02}:00 UTC", minute);
+            record
+        };
+
+        writer.write(&[message("m1", "alice", "first", 1), message("m2", "bob", "second", 2)]).unwrap();
+        writer
+            .write(&[
+                message("m2", "bob", "second", 2),
+                message("m3", "carol", "third", 3),
+                message("m4", "alice", "fourth", 4),
+            ])
+            .unwrap();
+
+        let content = fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap();
+        assert!(content.starts_with("# #general\n\n*4 messages*\n\n"), "{}", content);
+        for text in ["first", "second", "third", "fourth"] {
+            assert_eq!(content.matches(&format!("{}\n", text)).count(), 1, "{}", content);
+        }
+        assert!(content.find("second").unwrap() < content.find("third").unwrap());
+    }
+
+    #[test]
+    fn test_append_continues_the_last_day_divider() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                append: true,
+                day_dividers: true,
+                ..Default::default()
+            },
+        );
+        let message = |id: &str, content: &str, timestamp: &str| {
+            let mut record = record(id, "alice", content, None);
+            record.timestamp = timestamp.to_string();
+            record
+        };
+
+        writer.write(&[message("m1", "first", "2025-12-16 10:00:00 UTC")]).unwrap();
+        writer
+            .write(&[
+                message("m2", "same day", "2025-12-16 18:00:00 UTC"),
+                message("m3", "next day", "2025-12-17 09:00:00 UTC"),
+            ])
+            .unwrap();
+
+        let content = fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap();
+        assert_eq!(content.matches("## 2025-12-16\n").count(), 1, "{}", content);
+        assert_eq!(content.matches("## 2025-12-17\n").count(), 1, "{}", content);
+        assert!(content.find("same day").unwrap() < content.find("## 2025-12-17").unwrap());
+    }
+
+    #[test]
+    fn test_append_matches_content_exactly_in_grouped_files() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                append: true,
+                group_consecutive: true,
+                ..Default::default()
+            },
+        );
+        let message = |id: &str, content: &str, minute: u32| {
+            let mut record = record(id, "alice", content, None);
+            record.timestamp = format!("2025-12-16 10:{:02}:00 UTC", minute);
+            record
+        };
+        let first = [message("m1", "okay, thanks", 1), message("m2", "see you", 2)];
+
+        writer.write(&first).unwrap();
+        // A shorter text from the same author at a grouped message's time is still new
+        writer.write(&[first[0].clone(), first[1].clone(), message("m3", "ok", 2), message("m4", "see", 3)]).unwrap();
+        writer.write(&[message("m3", "ok", 2), message("m4", "see", 3)]).unwrap();
+
+        let content = fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap();
+        assert!(content.contains("*4 messages*"), "{}", content);
+        assert!(content.contains("<!-- sent 2025-12-16 10:02:00 UTC -->\nsee you\n"), "{}", content);
+        for text in ["okay, thanks\n", "see you\n", "\nok\n", "\nsee\n"] {
+            assert_eq!(content.matches(text).count(), 1, "{:?} in {}", text, content);
+        }
+    }
+
+    #[test]
+    fn test_nav_footer_links_adjacent_months_of_channel() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                nav_footer: true,
+                ..Default::default()
+            },
+        );
+        let mut records = Vec::new();
+        for (id, channel, timestamp) in [
+            ("m1", "general", "2025-10-15 12:00:00"),
+            ("m2", "general", "2025-11-15 12:00:00"),
+            ("m3", "general", "2025-12-15 12:00:00"),
+            ("m4", "random", "2025-11-15 12:00:00"),
+        ] {
+            let mut entry = record(id, "alice", "hello", None);
+            entry.channel_name = channel.to_string();
+            entry.timestamp = timestamp.to_string();
+            records.push(entry);
+        }
+
+        writer.write(&records).unwrap();
+
+        let middle = fs::read_to_string(dir.path().join("general-2025-11.md")).unwrap();
+        assert!(middle.ends_with("---\n\n[← Previous](general-2025-10.md) | [Next →](general-2025-12.md)\n"));
+        assert!(!dir.path().join("index.md").exists());
+        let last = fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap();
+        assert!(last.ends_with("[← Previous](general-2025-11.md)\n"));
+        let other = fs::read_to_string(dir.path().join("random-2025-11.md")).unwrap();
+        assert!(!other.contains("Previous") && !other.contains("Next"));
+    }
+
+    #[test]
+    fn test_max_file_bytes_splits_between_messages() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                max_file_bytes: Some(420),
+                ..Default::default()
+            },
+        );
+        let records: Vec<ExtractedRecord> = (1..=6)
+            .map(|n| record(&format!("m{}", n), "alice", &format!("message number {} {}", n, "x".repeat(40)), None))
+            .collect();
+
+        writer.write(&records).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert!(names.len() > 1);
+        assert_eq!(names[0], "general-2025-12.md");
+        assert_eq!(names[1], "general-2025-12.part2.md");
+
+        let parts: Vec<String> = names.iter().map(|name| fs::read_to_string(dir.path().join(name)).unwrap()).collect();
+        assert!(parts.iter().all(|part| part.len() <= 420), "{:?}", parts);
+        assert!(parts[0].contains(&format!("(part 1 of {})", names.len())));
+        assert!(parts[0].ends_with("*Continued in [general-2025-12.part2.md](general-2025-12.part2.md)*\n\n"));
+        assert!(parts[1].contains("*Continued from [general-2025-12.md](general-2025-12.md)*"));
+        // Every message appears whole in exactly one part
+        for entry in &records {
+            let holders = parts.iter().filter(|part| part.contains(&entry.content)).count();
+            assert_eq!(holders, 1, "{} should be in exactly one part", entry.content);
+        }
+    }
+
+    fn per_message_writer(dir: &TempDir, max_files: Option<usize>) -> MarkdownWriterAdapter {
+        MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                grouping: Grouping::Message,
+                max_files,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_one_file_per_message_paths_and_contents() {
+        let dir = TempDir::new().unwrap();
+        let mut first = record("m1", "alice", "first post", None);
+        first.timestamp = "2025-12-15 12:00:00".to_string();
+        let mut second = record("m2", "bob", "second post", None);
+        second.timestamp = "2025-12-16 12:00:00".to_string();
+        second.channel_name = "dev/ops".to_string();
+        let mut without_id = record("m3", "carol", "no id", None);
+        without_id.message_id = None;
+
+        per_message_writer(&dir, None).write(&[first, second, without_id]).unwrap();
+
+        let first_content = fs::read_to_string(dir.path().join("general/2025-12-15/m1.md")).unwrap();
+        assert_eq!(first_content, "**alice** *2025-12-15 12:00:00*\n\nfirst post\n");
+        let second_content = fs::read_to_string(dir.path().join("dev-ops/2025-12-16/m2.md")).unwrap();
+        assert!(second_content.contains("second post"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_max_files_limit_rejects_export() {
+        let dir = TempDir::new().unwrap();
+        let records = vec![
+            record("m1", "alice", "one", None),
+            record("m2", "alice", "two", None),
+        ];
+
+        assert!(per_message_writer(&dir, Some(1)).write(&records).is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert!(per_message_writer(&dir, Some(2)).write(&records).is_ok());
+    }
+
+    #[test]
+    fn test_dry_run_estimate_matches_written_size() {
+        let dir = TempDir::new().unwrap();
+        let mut records = Vec::new();
+        for (id, channel, timestamp) in [
+            ("m1", "general", "2025-11-15 12:00:00"),
+            ("m2", "general", "2025-12-15 12:00:00"),
+            ("m3", "random", "2025-12-16 12:00:00"),
+        ] {
+            let mut entry = record(id, "alice", "héllo wörld, some longer content", None);
+            entry.channel_name = channel.to_string();
+            entry.timestamp = timestamp.to_string();
+            records.push(entry);
+        }
+        let options = MarkdownOptions {
+            index: true,
+            ..Default::default()
+        };
+        let measuring = Arc::new(MeasuringSink::default());
+
+        MarkdownWriterAdapter::with_sink(measuring.clone(), options.clone()).write(&records).unwrap();
+        MarkdownWriterAdapter::with_options(dir.path().to_string_lossy().into_owned(), options)
+            .write(&records)
+            .unwrap();
+
+        let written: usize = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len() as usize)
+            .sum();
+        let estimate = measuring.total();
+        assert!(estimate.abs_diff(written) * 100 <= written, "estimate {} vs written {}", estimate, written);
+        assert_eq!(measuring.sizes().len(), 4);
+    }
+
+    #[test]
+    fn test_files_are_split_by_month_in_the_configured_timezone() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                timezone: "America/New_York".parse().unwrap(),
+                ..Default::default()
+            },
+        );
+        let mut entry = record("m1", "alice", "late evening", None);
+        entry.timestamp = "2025-12-01T02:30:00Z".to_string();
+
+        writer.write(&[entry]).unwrap();
+
+        assert!(dir.path().join("general-2025-11.md").exists());
+        assert!(!dir.path().join("general-2025-12.md").exists());
+    }
+
+    #[test]
+    fn test_multiple_granularities_write_separate_file_sets() {
+        let dir = TempDir::new().unwrap();
+        let sink: Arc<dyn OutputSink> = Arc::new(DirectorySink::new(dir.path()));
+        let mut records = Vec::new();
+        for (id, timestamp) in [("m1", "2025-11-15 12:00:00"), ("m2", "2025-12-15 12:00:00")] {
+            let mut entry = record(id, "alice", "hello", None);
+            entry.timestamp = timestamp.to_string();
+            records.push(entry);
+        }
+
+        for granularity in [Granularity::Year, Granularity::Month] {
+            let options = MarkdownOptions {
+                granularity,
+                ..Default::default()
+            };
+            let prefixed = Arc::new(PrefixedSink::new(sink.clone(), granularity.name()));
+            MarkdownWriterAdapter::with_sink(prefixed, options).write(&records).unwrap();
+        }
+
+        let yearly = fs::read_to_string(dir.path().join("year/general-2025.md")).unwrap();
+        assert!(yearly.contains("*2 messages*"));
+        assert!(dir.path().join("month/general-2025-11.md").exists());
+        assert!(dir.path().join("month/general-2025-12.md").exists());
+        assert_eq!(fs::read_dir(dir.path().join("year")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_index_activity_sort_lists_busiest_channel_first() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                index: true,
+                channel_sort: ChannelSort::Activity,
+                nav_footer: true,
+                ..Default::default()
+            },
+        );
+        let mut records = Vec::new();
+        for (id, channel, timestamp) in [
+            ("m1", "announcements", "2025-12-15 12:00:00"),
+            ("m2", "random", "2025-11-15 12:00:00"),
+            ("m3", "random", "2025-12-15 12:00:00"),
+            ("m4", "random", "2025-12-16 12:00:00"),
+            ("m5", "general", "2025-12-15 12:00:00"),
+            ("m6", "general", "2025-12-16 12:00:00"),
+        ] {
+            let mut entry = record(id, "alice", "hello", None);
+            entry.channel_name = channel.to_string();
+            entry.timestamp = timestamp.to_string();
+            records.push(entry);
+        }
+
+        writer.write(&records).unwrap();
+
+        let index = fs::read_to_string(dir.path().join("index.md")).unwrap();
+        let position = |heading: &str| index.find(heading).unwrap();
+        assert!(position("## #random") < position("## #general"));
+        assert!(position("## #general") < position("## #announcements"));
+        assert!(index.contains("- [2025-11](random-2025-11.md) (1 messages)\n- [2025-12](random-2025-12.md) (2 messages)\n"));
+
+        let footer = fs::read_to_string(dir.path().join("random-2025-12.md")).unwrap();
+        assert!(footer.ends_with("[← Previous](random-2025-11.md) | [↑ Index](index.md)\n"));
+    }
+
+    #[test]
+    fn test_raw_timestamps_render_stored_string() {
+        let writer = MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                raw_timestamps: true,
+                reply_depth: 1,
+                ..Default::default()
+            },
+        );
+        let mut root = record("m1", "alice", "root", None);
+        root.raw_timestamp = "2025-12-16T10:30:00.123+00:00".to_string();
+        let mut reply = record("m2", "bob", "reply", Some("m1"));
+        reply.raw_timestamp = "1734345000".to_string();
+
+        let output = render(&writer, &[root, reply]);
+
+        assert!(output.contains("> **alice** *2025-12-16T10:30:00.123+00:00*\n"));
+        assert!(output.contains("**bob** *1734345000*\n\n"));
+        assert!(!output.contains("2025-12-16 10:30:00 UTC"));
+    }
+
+    #[test]
+    fn test_message_numbers_continue_across_monthly_files() {
+        let dir = TempDir::new().unwrap();
+        let mut records = Vec::new();
+        for (id, timestamp) in [
+            ("m1", "2025-11-29 12:00:00"),
+            ("m2", "2025-11-30 12:00:00"),
+            ("m3", "2025-12-01 12:00:00"),
+        ] {
+            let mut entry = record(id, "alice", id, None);
+            entry.timestamp = timestamp.to_string();
+            records.push(entry);
+        }
+        let service = ExtractionServiceImpl::new(
+            Box::new(Records(records)),
+            Box::new(MarkdownWriterAdapter::new(dir.path().to_string_lossy().into_owned())),
+        )
+        .with_options(ExtractionOptions {
+            number_messages: true,
+            ..Default::default()
+        });
+
+        service.execute_extraction().unwrap();
+
+        let november = fs::read_to_string(dir.path().join("general-2025-11.md")).unwrap();
+        assert!(november.contains("**alice** *2025-11-29 12:00:00* #1\n\nm1"));
+        assert!(november.contains("**alice** *2025-11-30 12:00:00* #2\n\nm2"));
+        let december = fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap();
+        assert!(december.contains("**alice** *2025-12-01 12:00:00* #3\n\nm3"));
+    }
+
+    #[test]
+    fn test_normalize_unicode_groups_nfc_and_nfd_channel_names() {
+        let in_channel = |id: &str, channel: &str| ExtractedRecord {
+            channel_name: channel.to_string(),
+            ..record(id, "alice", id, None)
+        };
+        // "café" precomposed (NFC) and with a combining acute accent (NFD)
+        let records = vec![in_channel("m1", "caf\u{e9}"), in_channel("m2", "cafe\u{301}")];
+        let write = |normalize_unicode: bool| {
+            let dir = TempDir::new().unwrap();
+            let service = ExtractionServiceImpl::new(
+                Box::new(Records(records.clone())),
+                Box::new(MarkdownWriterAdapter::new(dir.path().to_string_lossy().into_owned())),
+            )
+            .with_options(ExtractionOptions {
+                normalize_unicode,
+                ..Default::default()
+            });
+            service.execute_extraction().unwrap();
+            let names: Vec<String> = fs::read_dir(dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names
+        };
+
+        assert_eq!(write(false).len(), 2);
+        assert_eq!(write(true), vec!["caf\u{e9}-2025-12.md"]);
+    }
+
+    #[test]
+    fn test_deleted_message_renders_distinct_placeholder() {
+        let mut deleted = record("m1", "alice", "", None);
+        deleted.deleted = true;
+        let empty = record("m2", "bob", "   ", None);
+
+        let output = render(&reply_writer(0), &[deleted, empty]);
+
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC*\n\n*[deleted]*\n\n"));
+        assert!(output.contains("**bob** *2025-12-16 10:30:00 UTC*\n\n*[No content]*\n\n"));
+    }
+
+    fn grouping_writer(group_window: Option<u64>) -> MarkdownWriterAdapter {
+        MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                group_consecutive: true,
+                group_window,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_forwarded_message_is_labelled() {
+        let mut forwarded = record("m2", "alice", "shared news", None);
+        forwarded.forwarded = true;
+        let records = vec![record("m1", "alice", "own words", None), forwarded];
+
+        let output = render(&grouping_writer(None), &records);
+
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC*\n\nown words"));
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC* ↪ forwarded\n\nshared news"));
+    }
+
+    #[test]
+    fn test_embed_images_renders_only_images_inline() {
+        let mut entry = record("m1", "alice", "see files", None);
+        entry.attachments = vec![
+            "https://cdn.example.com/a/photo.PNG?width=400".to_string(),
+            "https://cdn.example.com/a/report.pdf".to_string(),
+            "Yes".to_string(),
+        ];
+        let writer = |embed_images: bool| {
+            MarkdownWriterAdapter::with_options(
+                String::new(),
+                MarkdownOptions {
+                    embed_images,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let embedded = render(&writer(true), std::slice::from_ref(&entry));
+        assert!(embedded.contains(
+            "see files\n\n![photo.PNG](https://cdn.example.com/a/photo.PNG?width=400)\n\
+             [report.pdf](https://cdn.example.com/a/report.pdf)\n\n"
+        ));
+
+        let linked = render(&writer(false), &[entry]);
+        assert!(linked.contains("\n[photo.PNG](https://cdn.example.com/a/photo.PNG?width=400)\n"));
+        assert!(!linked.contains("Yes"));
+    }
+
+    #[test]
+    fn test_duplicate_message_is_labelled() {
+        let mut duplicate = record("m1", "alice", "buy now", None);
+        duplicate.duplicate = true;
+
+        let output = render(&MarkdownWriterAdapter::new(String::new()), &[duplicate]);
+
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC* ⚠ duplicate\n\nbuy now"));
+    }
+
+    #[test]
+    fn test_group_consecutive_collapses_same_author_headers() {
+        let records = vec![
+            record("m1", "alice", "first", None),
+            record("m2", "alice", "second", None),
+            record("m3", "bob", "third", None),
+        ];
+
+        let output = render(&grouping_writer(None), &records);
+
+        assert_eq!(output.matches("**alice**").count(), 1);
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC*\n\nfirst\n\nsecond\n\n---\n\n**bob**"));
+    }
+
+    #[test]
+    fn test_group_window_splits_messages_beyond_gap() {
+        let mut first = record("m1", "alice", "first", None);
+        first.timestamp = "2025-12-16 10:00:00 UTC".to_string();
+        let mut within = record("m2", "alice", "within", None);
+        within.timestamp = "2025-12-16 10:05:00 UTC".to_string();
+        let mut beyond = record("m3", "alice", "beyond", None);
+        beyond.timestamp = "2025-12-16 10:11:00 UTC".to_string();
+
+        let output = render(&grouping_writer(Some(5)), &[first, within, beyond]);
+
+        assert!(output.contains("first\n\nwithin\n\n---\n\n**alice** *2025-12-16 10:11:00 UTC*\n\nbeyond"));
+        assert_eq!(output.matches("**alice**").count(), 2);
+    }
+
+    #[test]
+    fn test_wrap_option_wraps_message_content() {
+        let writer = MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                wrap: Some(10),
+                ..Default::default()
+            },
+        );
+        let records = vec![record("m1", "alice", "one two three four", None)];
+
+        let output = render(&writer, &records);
+
+        assert!(output.contains("one two\nthree four\n\n"));
+    }
+
+    #[test]
+    fn test_first_line_as_title_only_for_multi_line_messages() {
+        let writer = MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                first_line_as_title: true,
+                ..Default::default()
+            },
+        );
+        let records = vec![
+            record("m1", "alice", "Release notes\nEverything shipped.", None),
+            record("m2", "bob", "just one line", None),
+        ];
+
+        let output = render(&writer, &records);
+
+        assert!(output.contains("**Release notes**\n\nEverything shipped.\n\n"));
+        assert!(output.contains("\n\njust one line\n\n"));
+        assert!(!output.contains("**just one line**"));
+    }
+
+    #[test]
+    fn test_header_tops_every_file() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                header: Some("Exported under server rules.\n".to_string()),
+                index: true,
+                ..Default::default()
+            },
+        );
+        let mut other = record("m2", "bob", "elsewhere", None);
+        other.channel_name = "random".to_string();
+        let records = vec![record("m1", "alice", "hello", None), other];
+
+        writer.write(&records).unwrap();
+
+        let mut files: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().path()).collect();
+        files.sort();
+        assert_eq!(files.len(), 3);
+        for path in files {
+            let content = fs::read_to_string(&path).unwrap();
+            assert!(content.starts_with("Exported under server rules.\n\n#"), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_relative_time_follows_absolute_timestamp() {
+        let writer = |now: &str| {
+            MarkdownWriterAdapter::with_options(
+                String::new(),
+                MarkdownOptions {
+                    relative_to: Some(now.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        let records = vec![record("m1", "alice", "hello", None)];
+
+        let output = render(&writer("2026-03-20T10:30:00Z"), &records);
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC (3 months ago)*"));
+
+        let output = render(&writer("2025-12-16T13:00:00Z"), &records);
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC (2 hours ago)*"));
+    }
+
+    #[test]
+    fn test_obsidian_vault_layout_tags_and_wikilinks() {
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::with_options(
+            dir.path().to_string_lossy().into_owned(),
+            MarkdownOptions {
+                obsidian: true,
+                anchors: true,
+                nav_footer: true,
+                reply_depth: 1,
+                ..Default::default()
+            },
+        );
+        let mut earlier = record("m0", "carol", "last month", None);
+        earlier.timestamp = "2025-11-20 09:00:00 UTC".to_string();
+        let records = vec![
+            earlier,
+            record("m1", "alice", "question", None),
+            record("m2", "bob", "answer", Some("m1")),
+        ];
+
+        writer.write(&records).unwrap();
+
+        let december = fs::read_to_string(dir.path().join("general/2025-12.md")).unwrap();
+        assert!(december.starts_with("---\ntags: [channel/general]\n---\n\n# #general\n"));
+        assert!(december.contains("**alice** *2025-12-16 10:30:00 UTC* ^msg-m1\n"));
+        assert!(december.contains("> **alice** *2025-12-16 10:30:00 UTC* [[#^msg-m1|↩]]\n"));
+        assert!(december.contains("[[general/2025-11|← Previous]]"));
+        assert!(!december.contains("<a id="));
+        assert!(dir.path().join("general/2025-11.md").exists());
+    }
+
+    #[test]
+    fn test_day_dividers_mark_each_date_change() {
+        let writer = MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                day_dividers: true,
+                group_consecutive: true,
+                ..Default::default()
+            },
+        );
+        let mut records = vec![
+            record("m1", "alice", "morning", None),
+            record("m2", "alice", "evening", None),
+            record("m3", "alice", "next day", None),
+        ];
+        records[0].timestamp = "2025-12-15 09:00:00 UTC".to_string();
+        records[1].timestamp = "2025-12-15 21:00:00 UTC".to_string();
+        records[2].timestamp = "2025-12-16 10:30:00 UTC".to_string();
+
+        let output = render(&writer, &records);
+
+        assert_eq!(output.matches("## 2025-12-15").count(), 1);
+        assert_eq!(output.matches("## 2025-12-16").count(), 1);
+        let divider = output.find("## 2025-12-16").unwrap();
+        assert!(output.find("evening").unwrap() < divider);
+        assert!(divider < output.find("next day").unwrap());
+        assert!(output[divider..].starts_with("## 2025-12-16\n\n**alice**"));
+    }
+
+    #[test]
+    fn test_detected_language_renders_as_tag() {
+        let writer = MarkdownWriterAdapter::new(String::new());
+        let mut records = vec![record("m1", "alice", "hola a todos", None), record("m2", "bob", "ok", None)];
+        records[0].language = Some("spa".to_string());
+
+        let output = render(&writer, &records);
+
+        assert!(output.contains("**alice** *2025-12-16 10:30:00 UTC* `spa`\n"));
+        assert!(output.contains("**bob** *2025-12-16 10:30:00 UTC*\n"));
+    }
+
+    #[test]
+    fn test_single_file_sections_channels_and_months() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("export.md");
+        let writer = MarkdownWriterAdapter::new_single_file(path.to_string_lossy().into_owned());
+        let mut november = record("m1", "alice", "autumn", None);
+        november.timestamp = "2025-11-20 09:00:00 UTC".to_string();
+        let mut random = record("m3", "carol", "elsewhere", None);
+        random.channel_name = "random".to_string();
+        let records = vec![random, record("m2", "bob", "winter", None), november];
+
+        writer.write(&records).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let headings: Vec<&str> = content.lines().filter(|line| line.starts_with('#')).collect();
+        assert_eq!(headings, vec!["# #general", "## 2025-11", "## 2025-12", "# #random", "## 2025-12"]);
+        assert!(content.starts_with("# #general\n\n## 2025-11\n\n*1 messages*\n\n---\n\n**alice**"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_single_file_writes_nothing_for_no_records() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("export.md");
+
+        MarkdownWriterAdapter::new_single_file(path.to_string_lossy().into_owned()).write(&[]).unwrap();
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_month_boundary_groups_and_orders_by_parsed_time() {
+        use chrono::{Local, TimeZone};
+
+        let dir = TempDir::new().unwrap();
+        let writer = MarkdownWriterAdapter::new(dir.path().to_string_lossy().into_owned());
+        // The last local minute of the year, written with a +14:00 offset so its text sorts
+        // after the first local minutes of January and, read as a prefix, names January
+        let last_minute = Local.with_ymd_and_hms(2025, 12, 31, 23, 59, 0).unwrap();
+        let mut boundary = record("m1", "alice", "last of december", None);
+        boundary.timestamp = last_minute.with_timezone(&chrono::FixedOffset::east_opt(14 * 3600).unwrap()).to_rfc3339();
+        let mut january = record("m2", "bob", "first of january", None);
+        january.timestamp = Local.with_ymd_and_hms(2026, 1, 1, 0, 1, 0).unwrap().to_rfc3339();
+        let mut earlier = record("m3", "carol", "earlier in december", None);
+        earlier.timestamp = Local.with_ymd_and_hms(2025, 12, 31, 22, 0, 0).unwrap().to_rfc3339();
+
+        // Source order follows the stored strings, as an ORDER BY on the raw column would
+        writer.write(&[january, boundary, earlier]).unwrap();
+
+        let december = fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap();
+        let positions = ["earlier in december", "last of december"].map(|text| december.find(text).unwrap());
+        assert!(positions[0] < positions[1]);
+        let january = fs::read_to_string(dir.path().join("general-2026-01.md")).unwrap();
+        assert!(january.contains("first of january") && !january.contains("december"));
+    }
+
+    #[test]
+    fn test_jump_links_and_attachments_render_under_messages() {
+        let writer = MarkdownWriterAdapter::with_options(
+            String::new(),
+            MarkdownOptions {
+                jump_links: true,
+                group_consecutive: true,
+                ..Default::default()
+            },
+        );
+        let mut with_files = record("m1", "alice", "", None);
+        with_files.jump_url = Some("https://discord.com/channels/1/2/m1".to_string());
+        with_files.attachments = vec!["https://cdn/a.png".to_string(), "https://cdn/b.txt".to_string()];
+        let mut follow_up = record("m2", "alice", "see above", None);
+        follow_up.jump_url = Some("https://discord.com/channels/1/2/m2".to_string());
+        let plain = record("m3", "bob", "no link", None);
+
+        let output = render(&writer, &[with_files, follow_up, plain]);
+
+        assert!(output.contains(
+            "**alice** *2025-12-16 10:30:00 UTC* [↗](https://discord.com/channels/1/2/m1)\n\n[a.png](https://cdn/a.png)\n[b.txt](https://cdn/b.txt)\n\nsee above\n\n[↗](https://discord.com/channels/1/2/m2)\n\n"
+        ));
+        assert!(output.contains("**bob** *2025-12-16 10:30:00 UTC*\n\nno link"));
+        assert!(!output.contains("[No content]"));
+    }
+}
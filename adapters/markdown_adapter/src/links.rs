@@ -0,0 +1,66 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use core::utils::extract_links;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Writes every link found in message content to a plain text file, one per line in record order
+pub struct LinksWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+}
+
+impl LinksWriter {
+    pub fn new(output_path: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new("")), output_path)
+    }
+
+    /// Creates a links writer emitting `output_path` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>, output_path: String) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(output_path),
+        }
+    }
+}
+
+impl MarkdownWriter for LinksWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let content: String = records
+            .iter()
+            .flat_map(|record| extract_links(&record.content))
+            .map(|link| format!("{}\n", link))
+            .collect();
+
+        self.sink.write_file(&self.output_path, content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_links_file_lists_each_link() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("links.txt");
+        let writer = LinksWriter::new(path.to_string_lossy().into_owned());
+        let record = |content: &str| ExtractedRecord {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let records = vec![
+            record("two links: https://a.example/1 and http://b.example/2"),
+            record("nothing here"),
+            record("one more https://c.example"),
+        ];
+
+        writer.write(&records).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "https://a.example/1\nhttp://b.example/2\nhttps://c.example\n");
+    }
+}
@@ -0,0 +1,272 @@
+use crate::WEEKDAYS;
+use core::ports::Result;
+use core::utils::Granularity;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What to do when the output folder already has content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExistingOutput {
+    /// Refuse to write into a non-empty folder
+    #[default]
+    Fail,
+    /// Write over matching files and leave everything else in place
+    Overwrite,
+    /// Delete files this tool generates before writing; unrelated files are kept
+    Clean,
+}
+
+impl FromStr for ExistingOutput {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "fail" => Ok(Self::Fail),
+            "overwrite" => Ok(Self::Overwrite),
+            "clean" => Ok(Self::Clean),
+            other => Err(format!("unknown existing-output policy '{}' (expected fail, overwrite or clean)", other)),
+        }
+    }
+}
+
+/// Applies the policy to `folder` before any file is written
+/// A folder that does not exist yet counts as empty; an existing file is rejected up front
+pub fn prepare_output_folder(folder: &Path, policy: ExistingOutput) -> Result<()> {
+    if !folder.exists() {
+        return Ok(());
+    }
+    if !folder.is_dir() {
+        return Err(format!("output folder {} is an existing file, not a directory", folder.display()).into());
+    }
+
+    match policy {
+        ExistingOutput::Fail => {
+            if fs::read_dir(folder)?.next().is_some() {
+                return Err(format!(
+                    "output folder {} is not empty (use --clean or --overwrite)",
+                    folder.display()
+                )
+                .into());
+            }
+        }
+        ExistingOutput::Overwrite => {}
+        ExistingOutput::Clean => {
+            let removed = clean_output_folder(folder)?;
+            log::info!(count = removed; "removed previous output files");
+        }
+    }
+    Ok(())
+}
+
+/// Checks a single-file output path before any work: it may exist as a file, but not as a directory
+pub fn check_output_file(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        return Err(format!("output file {} is an existing directory", path.display()).into());
+    }
+    Ok(())
+}
+
+/// Removes the Markdown files this tool generates from `folder` and returns how many were removed
+/// Covers channel-period (day, month or year), weekday and index files (and their `.partN.md`
+/// parts) at the top level, the same files in the `day/`, `month/` and `year/` directories
+/// written for several granularities, and per-message files in `<channel>/<YYYY-MM-DD>/`
+/// directories; directories are removed once empty
+pub fn clean_output_folder(folder: &Path) -> Result<usize> {
+    let mut removed = 0;
+
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if entry.file_type()?.is_dir() {
+            if name.parse::<Granularity>().is_ok() {
+                removed += clean_output_folder(&path)?;
+                let _ = fs::remove_dir(&path);
+            } else {
+                removed += clean_channel_directory(&path)?;
+            }
+        } else if is_generated_filename(&name) {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Removes per-message files from the date directories of one channel directory
+fn clean_channel_directory(channel_dir: &Path) -> Result<usize> {
+    let mut removed = 0;
+
+    for entry in fs::read_dir(channel_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !entry.file_type()?.is_dir() || !is_date(&name) {
+            continue;
+        }
+        for file in fs::read_dir(entry.path())? {
+            let file = file?;
+            if file.file_type()?.is_file() && file.file_name().to_string_lossy().ends_with(".md") {
+                fs::remove_file(file.path())?;
+                removed += 1;
+            }
+        }
+        // Keep directories that still hold unrelated files
+        let _ = fs::remove_dir(entry.path());
+    }
+    let _ = fs::remove_dir(channel_dir);
+
+    Ok(removed)
+}
+
+/// Whether a top-level file name matches one of the generated layouts
+fn is_generated_filename(name: &str) -> bool {
+    let Some(stem) = name.strip_suffix(".md") else {
+        return false;
+    };
+    // Parts after the first of a split file are `<stem>.partN.md`
+    let stem = match stem.rsplit_once(".part") {
+        Some((base, number)) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => stem,
+    };
+    if stem == "index" || stem == "unknown" || WEEKDAYS.contains(&stem) {
+        return true;
+    }
+    // `<channel>-<YYYY-MM-DD>`, `<channel>-<YYYY-MM>`, `<channel>-<YYYY>` or `<channel>-unknown`
+    let period = |len: usize| {
+        let start = stem.len().checked_sub(len + 1).filter(|&start| start > 0 && stem.is_char_boundary(start))?;
+        stem[start..].strip_prefix('-')
+    };
+    let is_channel_period = period(10).is_some_and(is_date)
+        || period(7).is_some_and(is_year_month)
+        || period(4).is_some_and(is_year);
+    is_channel_period || stem.strip_suffix("-unknown").is_some_and(|channel| !channel.is_empty())
+}
+
+fn is_year(value: &str) -> bool {
+    value.len() == 4 && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_year_month(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 7 && bytes[4] == b'-' && bytes.iter().enumerate().all(|(i, b)| i == 4 || b.is_ascii_digit())
+}
+
+fn is_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && is_year_month(&value[..7])
+        && bytes[7] == b'-'
+        && bytes[8..].iter().all(u8::is_ascii_digit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn touch(dir: &TempDir, relative: &str) {
+        let path = dir.path().join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x").unwrap();
+    }
+
+    #[test]
+    fn test_clean_removes_only_generated_files() {
+        let dir = TempDir::new().unwrap();
+        for generated in [
+            "general-2025-11.md",
+            "general-2025-11.part2.md",
+            "general-2025-12-16.md",
+            "general-2025.md",
+            "dev-ops-unknown.md",
+            "monday.md",
+            "index.md",
+            "general/2025-12-16/m1.md",
+            "day/general-2025-12-16.md",
+            "month/general-2025-12.md",
+            "year/general-2025.md",
+        ] {
+            touch(&dir, generated);
+        }
+        for unrelated in [
+            "README.md",
+            "notes.txt",
+            "general-2025-11.md.bak",
+            "general-25.md",
+            "general/2025-12-16/keep.txt",
+            "year/notes.txt",
+        ] {
+            touch(&dir, unrelated);
+        }
+
+        prepare_output_folder(dir.path(), ExistingOutput::Clean).unwrap();
+
+        let mut remaining: Vec<String> = walk(dir.path());
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                "README.md",
+                "general-2025-11.md.bak",
+                "general-25.md",
+                "general/2025-12-16/keep.txt",
+                "notes.txt",
+                "year/notes.txt",
+            ]
+        );
+        assert!(!dir.path().join("day").exists());
+    }
+
+    #[test]
+    fn test_fail_on_nonempty_guard() {
+        let dir = TempDir::new().unwrap();
+        assert!(prepare_output_folder(dir.path(), ExistingOutput::Fail).is_ok());
+        assert!(prepare_output_folder(&dir.path().join("missing"), ExistingOutput::Fail).is_ok());
+
+        touch(&dir, "general-2025-11.md");
+
+        assert!(prepare_output_folder(dir.path(), ExistingOutput::Fail).is_err());
+        assert!(prepare_output_folder(dir.path(), ExistingOutput::Overwrite).is_ok());
+        assert!(dir.path().join("general-2025-11.md").exists());
+    }
+
+    #[test]
+    fn test_output_folder_rejects_existing_file() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "export.md");
+
+        for policy in [ExistingOutput::Fail, ExistingOutput::Overwrite, ExistingOutput::Clean] {
+            let error = prepare_output_folder(&dir.path().join("export.md"), policy).unwrap_err();
+            assert!(error.to_string().contains("is an existing file, not a directory"));
+        }
+        assert!(dir.path().join("export.md").exists());
+    }
+
+    #[test]
+    fn test_output_file_rejects_existing_directory() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "summary.json");
+
+        assert!(check_output_file(&dir.path().join("summary.json")).is_ok());
+        assert!(check_output_file(&dir.path().join("new.json")).is_ok());
+        let error = check_output_file(dir.path()).unwrap_err();
+        assert!(error.to_string().contains("is an existing directory"));
+    }
+
+    fn walk(root: &Path) -> Vec<String> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(root).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                files.extend(walk(&path).into_iter().map(|child| format!("{}/{}", name, child)));
+            } else {
+                files.push(path.file_name().unwrap().to_string_lossy().into_owned());
+            }
+        }
+        files
+    }
+}
@@ -0,0 +1,79 @@
+use crate::roster::escape_csv_field;
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::sinks::DirectorySink;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Writes every reaction of the extracted records as CSV: one `message_id,emoji,count` row
+/// per emoji per message, in record order
+pub struct ReactionsCsvWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+}
+
+impl ReactionsCsvWriter {
+    pub fn new(output_path: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new("")), output_path)
+    }
+
+    /// Creates a reactions writer emitting `output_path` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>, output_path: String) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(output_path),
+        }
+    }
+}
+
+impl MarkdownWriter for ReactionsCsvWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let mut output = String::from("message_id,emoji,count\n");
+        for record in records {
+            let message_id = record.message_id.as_deref().unwrap_or("");
+            for reaction in &record.reactions {
+                output.push_str(&format!(
+                    "{},{},{}\n",
+                    escape_csv_field(message_id),
+                    escape_csv_field(&reaction.emoji),
+                    reaction.count
+                ));
+            }
+        }
+
+        self.sink.write_file(&self.output_path, output.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::utils::parse_reactions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(message_id: &str, reactions: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            message_id: Some(message_id.to_string()),
+            reactions: parse_reactions(reactions),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reactions_csv_has_one_row_per_emoji() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("reactions.csv");
+        let writer = ReactionsCsvWriter::new(path.to_string_lossy().into_owned());
+        let records = vec![
+            record("m1", r#"[{"emoji": "👍", "count": 3}, {"emoji": "tada", "count": 1}]"#),
+            record("m2", ""),
+            record("m3", r#"[{"emoji": "a,b", "count": 2}]"#),
+        ];
+
+        writer.write(&records).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "message_id,emoji,count\nm1,👍,3\nm1,tada,1\nm3,\"a,b\",2\n");
+    }
+}
@@ -0,0 +1,137 @@
+use core::domain::ExtractedRecord;
+use core::ports::{MarkdownWriter, OutputSink, Result};
+use core::roster::{build_roster, RosterEntry};
+use core::sinks::DirectorySink;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Writes the unique authors of the extracted records to a single file
+/// The format follows the file extension: `.csv` for CSV, Markdown otherwise
+pub struct RosterWriter {
+    sink: Arc<dyn OutputSink>,
+    output_path: PathBuf,
+}
+
+impl RosterWriter {
+    pub fn new(output_path: String) -> Self {
+        Self::with_sink(Arc::new(DirectorySink::new("")), output_path)
+    }
+
+    /// Creates a roster writer emitting `output_path` into the given sink
+    pub fn with_sink(sink: Arc<dyn OutputSink>, output_path: String) -> Self {
+        Self {
+            sink,
+            output_path: PathBuf::from(output_path),
+        }
+    }
+
+    fn is_csv(&self) -> bool {
+        self.output_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+    }
+
+    /// Formats the roster as a Markdown table
+    fn format_markdown(roster: &[RosterEntry]) -> String {
+        let mut output = String::new();
+        output.push_str("# Roster\n\n");
+        output.push_str(&format!("*{} authors*\n\n", roster.len()));
+        output.push_str("| Username | User ID |\n");
+        output.push_str("| --- | --- |\n");
+
+        for entry in roster {
+            output.push_str(&format!(
+                "| {} | {} |\n",
+                entry.username.replace('|', "\\|"),
+                entry.user_id.as_deref().unwrap_or("")
+            ));
+        }
+
+        output
+    }
+
+    /// Formats the roster as CSV with a header row
+    fn format_csv(roster: &[RosterEntry]) -> String {
+        let mut output = String::from("username,user_id\n");
+        for entry in roster {
+            output.push_str(&format!(
+                "{},{}\n",
+                escape_csv_field(&entry.username),
+                escape_csv_field(entry.user_id.as_deref().unwrap_or(""))
+            ));
+        }
+        output
+    }
+}
+
+/// Quotes a CSV field when it contains separators, quotes, or line breaks
+pub(crate) fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl MarkdownWriter for RosterWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let roster = build_roster(records);
+        let content = if self.is_csv() {
+            Self::format_csv(&roster)
+        } else {
+            Self::format_markdown(&roster)
+        };
+
+        self.sink.write_file(&self.output_path, content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn record(username: &str, user_id: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            username: username.to_string(),
+            user_id: Some(user_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn records() -> Vec<ExtractedRecord> {
+        vec![
+            record("zed", "u9"),
+            record("alice", "u1"),
+            record("zed", "u9"),
+            record("bob, jr", "u2"),
+        ]
+    }
+
+    #[test]
+    fn test_roster_csv_lists_each_author_once_sorted() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("roster.csv");
+        let writer = RosterWriter::new(path.to_string_lossy().into_owned());
+
+        writer.write(&records()).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "username,user_id\nalice,u1\n\"bob, jr\",u2\nzed,u9\n");
+    }
+
+    #[test]
+    fn test_roster_markdown_lists_each_author_once() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("roster.md");
+        let writer = RosterWriter::new(path.to_string_lossy().into_owned());
+
+        writer.write(&records()).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# Roster\n\n*3 authors*\n\n"));
+        assert_eq!(content.matches("| zed | u9 |").count(), 1);
+        assert!(content.find("| alice |").unwrap() < content.find("| zed |").unwrap());
+    }
+}
@@ -0,0 +1,70 @@
+//! Compares per-channel fetching with the prepared statement reused against re-preparing it
+//! for every channel. Run with `cargo bench -p sqlite_adapter`.
+
+use core::ports::DataRepository;
+use rusqlite::Connection;
+use sqlite_adapter::SqliteDataRepository;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const CHANNELS: usize = 500;
+const MESSAGES_PER_CHANNEL: usize = 20;
+const ROUNDS: usize = 5;
+
+/// Creates a database with many small channels, where preparing the query dominates
+fn create_database(dir: &TempDir) -> String {
+    let path = dir.path().join("bench.db");
+    let mut conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT);
+        CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+        CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+        CREATE INDEX messages_channel ON messages (channel_id);
+        INSERT INTO users VALUES ('u1', 'alice');
+        "#,
+    )
+    .unwrap();
+    let tx = conn.transaction().unwrap();
+    for channel in 0..CHANNELS {
+        tx.execute("INSERT INTO channels VALUES (?1, ?2)", (channel, format!("channel-{}", channel)))
+            .unwrap();
+        for message in 0..MESSAGES_PER_CHANNEL {
+            tx.execute(
+                "INSERT INTO messages VALUES (?1, 'u1', ?2, 'hello')",
+                (channel, format!("2025-12-16T10:{:02}:00Z", message % 60)),
+            )
+            .unwrap();
+        }
+    }
+    tx.commit().unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+/// Fetches every channel with a fresh repository and returns the fastest of several rounds
+fn time_fetch(path: &str, statement_cache_capacity: usize) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let repository =
+                SqliteDataRepository::new(path.to_string()).with_statement_cache_capacity(statement_cache_capacity);
+            let ids = repository.fetch_channel_ids().unwrap();
+            let start = Instant::now();
+            let records: usize = ids.iter().map(|id| repository.fetch_by_channel(id).unwrap().len()).sum();
+            assert_eq!(records, CHANNELS * MESSAGES_PER_CHANNEL);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let dir = TempDir::new().unwrap();
+    let path = create_database(&dir);
+
+    let reused = time_fetch(&path, 16);
+    let re_prepared = time_fetch(&path, 0);
+
+    println!("{} channels x {} messages, best of {} rounds", CHANNELS, MESSAGES_PER_CHANNEL, ROUNDS);
+    println!("prepared once, re-bound: {:>10.2?}", reused);
+    println!("re-prepared per channel: {:>10.2?}", re_prepared);
+}
@@ -1,57 +1,1117 @@
 use core::domain::ExtractedRecord;
 use core::ports::{DataRepository, Result};
-use core::utils::format_timestamp_to_local;
-use rusqlite::{Connection, Row};
+use core::summary::{ChannelRange, ChannelRanges};
+use core::utils::{extract_year_month, format_timestamp_in, parse_attachments, parse_reactions, OutputTimezone};
+use rusqlite::{params_from_iter, Connection, OpenFlags, Row};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+pub mod schema;
+
+pub use schema::SchemaConfig;
+
+/// Names keyed by id, e.g. usernames by user id
+type NameMap = HashMap<String, String>;
+
+/// Prepared statements SQLite keeps per connection by default (rusqlite's own default)
+const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 16;
+
+/// Connection and query text reused across `fetch_by_channel` calls
+struct ChannelFetch {
+    conn: Connection,
+    query: String,
+}
 
 /// SQLite implementation of the DataRepository trait
 pub struct SqliteDataRepository {
     db_path: String,
+    schema: SchemaConfig,
+    top_channels: Option<usize>,
+    statement_cache_capacity: usize,
+    timezone: OutputTimezone,
+    // Built on first use and shared for the rest of the run, so lookups don't re-query
+    usernames: Mutex<Option<Arc<NameMap>>>,
+    channel_names: Mutex<Option<Arc<NameMap>>>,
+    channel_fetch: Mutex<Option<ChannelFetch>>,
 }
 
 impl SqliteDataRepository {
     /// Creates a new SqliteDataRepository with the given database path
     pub fn new(db_path: String) -> Self {
-        Self { db_path }
+        Self {
+            db_path,
+            schema: SchemaConfig::default(),
+            top_channels: None,
+            statement_cache_capacity: DEFAULT_STATEMENT_CACHE_CAPACITY,
+            timezone: OutputTimezone::default(),
+            usernames: Mutex::new(None),
+            channel_names: Mutex::new(None),
+            channel_fetch: Mutex::new(None),
+        }
+    }
+
+    /// Creates a repository reading a database with renamed tables and columns
+    /// Fails if a name in `schema` is not a plain identifier (see `SchemaConfig::validate`)
+    pub fn new_with_schema(db_path: String, schema: SchemaConfig) -> Result<Self> {
+        schema.validate()?;
+        Ok(Self {
+            schema,
+            ..Self::new(db_path)
+        })
+    }
+
+    /// Usernames keyed by user id, queried once per repository and then shared
+    pub fn usernames(&self) -> Result<Arc<NameMap>> {
+        let s = &self.schema;
+        cached(&self.usernames, || {
+            self.query_names(&format!(
+                "SELECT CAST({id} AS TEXT), {name} FROM {table} WHERE {id} IS NOT NULL AND {name} IS NOT NULL",
+                id = s.user_id,
+                name = s.username,
+                table = s.users_table,
+            ))
+        })
+    }
+
+    /// Channel names keyed by channel id, queried once per repository and then shared
+    pub fn channel_names(&self) -> Result<Arc<NameMap>> {
+        let s = &self.schema;
+        cached(&self.channel_names, || {
+            self.query_names(&format!(
+                "SELECT CAST({id} AS TEXT), {name} FROM {table} WHERE {id} IS NOT NULL AND {name} IS NOT NULL",
+                id = s.channel_id,
+                name = s.channel_name,
+                table = s.channels_table,
+            ))
+        })
+    }
+
+    /// Runs a query selecting (id, name) pairs into a map
+    fn query_names(&self, query: &str) -> Result<NameMap> {
+        let conn = self.open_connection()?;
+        let mut stmt = conn.prepare(query)?;
+        let names = stmt
+            .query_map([], |row: &Row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<NameMap, rusqlite::Error>>()?;
+
+        Ok(names)
+    }
+
+    /// Restricts extraction to the `limit` channels with the most messages
+    /// Ties are broken by channel name so the selection is deterministic
+    pub fn with_top_channels(mut self, limit: usize) -> Self {
+        self.top_channels = Some(limit);
+        self
+    }
+
+    /// Caps how many prepared statements the per-channel fetch keeps open for reuse
+    /// 0 disables reuse, so every channel re-prepares its query
+    pub fn with_statement_cache_capacity(mut self, capacity: usize) -> Self {
+        self.statement_cache_capacity = capacity;
+        self
+    }
+
+    /// Formats timestamps in this timezone instead of the local one
+    pub fn with_timezone(mut self, timezone: OutputTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Opens the database, honouring SQLite URI parameters for `file:` paths
+    /// e.g. `file:archive.db?mode=ro&immutable=1`
+    fn open_connection(&self) -> rusqlite::Result<Connection> {
+        self.open_with_flags(OpenFlags::default())
+    }
+
+    fn open_with_flags(&self, flags: OpenFlags) -> rusqlite::Result<Connection> {
+        if self.db_path.starts_with("file:") {
+            Connection::open_with_flags(&self.db_path, flags | OpenFlags::SQLITE_OPEN_URI)
+        } else {
+            Connection::open_with_flags(&self.db_path, flags)
+        }
     }
 }
 
-impl DataRepository for SqliteDataRepository {
-    fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
-        // Connect to the SQLite database
-        let conn = Connection::open(&self.db_path)?;
+/// Expected versus found schema of one required table
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TableDiagnostics {
+    pub table: String,
+    /// False when the table does not exist at all
+    pub found: bool,
+    pub expected_columns: Vec<String>,
+    /// Columns present in the database, with their declared SQLite type
+    pub found_columns: BTreeMap<String, String>,
+    pub missing_columns: Vec<String>,
+}
+
+/// Result of checking whether a database looks extractable
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// Required tables or columns that are absent, as `table` or `table.column`
+    pub missing: Vec<String>,
+    /// Per-table schema details for machine consumption (see `to_json`)
+    pub tables: Vec<TableDiagnostics>,
+    /// Number of rows in the messages table (0 when it is missing)
+    pub message_count: usize,
+    /// Messages whose timestamp is empty or cannot be parsed
+    pub unparseable_timestamps: usize,
+    /// First and last parseable message timestamp of each channel
+    pub channel_ranges: BTreeMap<String, ChannelRange>,
+}
+
+impl ValidationReport {
+    /// True when the schema is complete and every timestamp parses
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.unparseable_timestamps == 0
+    }
+
+    /// Renders the report as pretty-printed JSON, for tooling that checks schemas automatically
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl SqliteDataRepository {
+    /// Checks the schema and timestamps without extracting anything
+    /// The database is opened read-only, so a missing file is an error rather than created
+    pub fn validate(&self) -> Result<ValidationReport> {
+        let conn = self.open_with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+        let mut report = ValidationReport::default();
+
+        let s = &self.schema;
+        for (table, columns) in s.required() {
+            let found_columns = column_types(&conn, table)?;
+            let missing_columns: Vec<String> = columns
+                .iter()
+                .filter(|column| !found_columns.contains_key(**column))
+                .map(|column| column.to_string())
+                .collect();
+            if found_columns.is_empty() {
+                report.missing.push(table.to_string());
+            } else {
+                report
+                    .missing
+                    .extend(missing_columns.iter().map(|column| format!("{}.{}", table, column)));
+            }
+            report.tables.push(TableDiagnostics {
+                table: table.to_string(),
+                found: !found_columns.is_empty(),
+                expected_columns: columns.iter().map(|column| column.to_string()).collect(),
+                found_columns,
+                missing_columns,
+            });
+        }
+
+        let is_missing = |names: &[String]| report.missing.iter().any(|item| names.contains(item));
+        let messages_usable =
+            !is_missing(&[s.messages_table.clone(), format!("{}.{}", s.messages_table, s.message_timestamp)]);
+        if messages_usable {
+            // Channel names need the join columns; without them every message counts as `Unknown`
+            let channels_usable = !is_missing(&[
+                s.channels_table.clone(),
+                format!("{}.{}", s.channels_table, s.channel_id),
+                format!("{}.{}", s.channels_table, s.channel_name),
+                format!("{}.{}", s.messages_table, s.message_channel_id),
+            ]);
+            let (channel, join) = if channels_usable {
+                (
+                    format!("COALESCE(c.{}, 'Unknown')", s.channel_name),
+                    format!("LEFT JOIN {} c ON m.{} = c.{}", s.channels_table, s.message_channel_id, s.channel_id),
+                )
+            } else {
+                ("'Unknown'".to_string(), String::new())
+            };
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {}, COALESCE(CAST(m.{} AS TEXT), '') FROM {} m {}",
+                channel, s.message_timestamp, s.messages_table, join
+            ))?;
+            let mut rows = stmt.query([])?;
+            let mut channel_ranges = ChannelRanges::default();
+            while let Some(row) = rows.next()? {
+                let channel: String = row.get(0)?;
+                let timestamp: String = row.get(1)?;
+                report.message_count += 1;
+                if extract_year_month(&timestamp).is_none() {
+                    report.unparseable_timestamps += 1;
+                }
+                channel_ranges.add(&channel, &timestamp);
+            }
+            report.channel_ranges = channel_ranges.finish();
+        }
+
+        Ok(report)
+    }
+}
+
+/// Returns the cached map, loading it first if needed
+/// The lock is held while loading, so concurrent callers wait for one query instead of racing
+fn cached(cache: &Mutex<Option<Arc<NameMap>>>, load: impl FnOnce() -> Result<NameMap>) -> Result<Arc<NameMap>> {
+    let mut cache = cache.lock().map_err(|_| "name cache lock poisoned")?;
+    if let Some(names) = cache.as_ref() {
+        return Ok(names.clone());
+    }
+    let names = Arc::new(load()?);
+    *cache = Some(names.clone());
+    Ok(names)
+}
+
+/// Returns the column names of a table (empty if the table does not exist)
+fn table_columns(conn: &Connection, table: &str) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row: &Row| row.get::<_, String>(1))?
+        .collect::<std::result::Result<HashSet<_>, rusqlite::Error>>()?;
+
+    Ok(columns)
+}
+
+/// Returns each column of a table with its declared type (empty if the table does not exist)
+fn column_types(conn: &Connection, table: &str) -> Result<BTreeMap<String, String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row: &Row| Ok((row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+        .collect::<std::result::Result<BTreeMap<_, _>, rusqlite::Error>>()?;
+
+    Ok(columns)
+}
+
+/// Builds a condition keeping only the `limit` busiest channels, ties broken by name
+fn top_channels_filter(schema: &SchemaConfig, limit: Option<usize>) -> Option<String> {
+    limit.map(|limit| {
+        format!(
+            r#"m.{channel_ref} IN (
+                SELECT tm.{channel_ref}
+                FROM {messages} tm
+                LEFT JOIN {channels} tc ON tm.{channel_ref} = tc.{channel_id}
+                GROUP BY tm.{channel_ref}
+                ORDER BY COUNT(*) DESC, COALESCE(tc.{channel_name}, '') ASC
+                LIMIT {limit}
+            )"#,
+            channel_ref = schema.message_channel_id,
+            messages = schema.messages_table,
+            channels = schema.channels_table,
+            channel_id = schema.channel_id,
+            channel_name = schema.channel_name,
+            limit = limit
+        )
+    })
+}
+
+/// Joins conditions into a WHERE clause, or nothing when there are none
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+}
+
+/// Selects an optional messages column as text, or NULL when the schema lacks it
+fn optional_column(columns: &HashSet<String>, name: &str) -> String {
+    if columns.contains(name) {
+        format!("CAST(m.{} AS TEXT)", name)
+    } else {
+        "NULL".to_string()
+    }
+}
+
+/// Selects an optional boolean messages column, or 0 when the schema lacks it
+fn optional_flag(columns: &HashSet<String>, name: &str) -> String {
+    if columns.contains(name) {
+        format!("COALESCE(m.{}, 0) != 0", name)
+    } else {
+        "0".to_string()
+    }
+}
+
+impl SqliteDataRepository {
+    /// Builds the extraction query, restricted to the channel bound as `?1` when `by_channel` is set
+    fn records_query(&self, conn: &Connection, by_channel: bool) -> Result<String> {
+        // Optional columns are selected as NULL when the archive doesn't have them
+        let s = &self.schema;
+        let message_columns = table_columns(conn, &s.messages_table)?;
+        let channel_columns = table_columns(conn, &s.channels_table)?;
+        let is_thread = if channel_columns.contains("parent_id") {
+            "c.parent_id IS NOT NULL"
+        } else {
+            "0"
+        };
+        // Jump links append the message id to the channel's URL, e.g. a Discord channel link
+        let jump_url = if channel_columns.contains("url") && message_columns.contains("message_id") {
+            "RTRIM(c.url, '/') || '/' || CAST(m.message_id AS TEXT)"
+        } else {
+            "NULL"
+        };
+        let category = if channel_columns.contains("category") {
+            "CAST(c.category AS TEXT)"
+        } else {
+            "NULL"
+        };
+
+        let mut conditions: Vec<String> = top_channels_filter(s, self.top_channels).into_iter().collect();
+        if by_channel {
+            conditions.push(format!("CAST(m.{} AS TEXT) = ?1", s.message_channel_id));
+        }
 
         // Execute a SQL JOIN query to pull channel_name, username, timestamp, and content
         // Ordered by timestamp ascending
-        let mut stmt = conn.prepare(
+        let query = format!(
             r#"
             SELECT 
-                COALESCE(c.name, 'Unknown') AS channel_name,
-                COALESCE(u.username, 'Unknown') AS username,
-                COALESCE(m.timestamp, '') AS timestamp,
-                COALESCE(m.content, '') AS content
-            FROM messages m
-            LEFT JOIN channels c ON m.channel_id = c.id
-            LEFT JOIN users u ON m.user_id = u.user_id
-            ORDER BY m.timestamp ASC
-            "#,
-        )?;
-
-        // Map rows to ExtractedRecord using rusqlite's row mapping
+                COALESCE(c.{}, 'Unknown') AS channel_name,
+                COALESCE(u.{}, 'Unknown') AS username,
+                COALESCE(m.{}, '') AS timestamp,
+                COALESCE(m.{}, '') AS content,
+                CAST(m.{} AS TEXT) AS user_id,
+                {} AS message_id,
+                {} AS reply_to,
+                {} AS deleted,
+                {} AS is_thread,
+                {} AS attachments,
+                CAST(m.{} AS TEXT) AS channel_id,
+                {} AS category,
+                {} AS reactions,
+                {} AS forwarded,
+                {} AS pinned,
+                {} AS jump_url
+            FROM {} m
+            LEFT JOIN {} c ON m.{} = c.{}
+            LEFT JOIN {} u ON m.{} = u.{}
+            {}
+            ORDER BY m.{} ASC
+            "#,
+            s.channel_name,
+            s.username,
+            s.message_timestamp,
+            s.message_content,
+            s.message_user_id,
+            optional_column(&message_columns, "message_id"),
+            optional_column(&message_columns, "reply_to"),
+            optional_flag(&message_columns, "deleted"),
+            is_thread,
+            optional_column(&message_columns, "attachments"),
+            s.message_channel_id,
+            category,
+            optional_column(&message_columns, "reactions"),
+            optional_flag(&message_columns, "forwarded"),
+            optional_flag(&message_columns, "pinned"),
+            jump_url,
+            s.messages_table,
+            s.channels_table,
+            s.message_channel_id,
+            s.channel_id,
+            s.users_table,
+            s.message_user_id,
+            s.user_id,
+            where_clause(&conditions),
+            s.message_timestamp,
+        );
+        Ok(query)
+    }
+
+    /// Fetches every record in timestamp order
+    fn query_records(&self) -> Result<Vec<ExtractedRecord>> {
+        let mut records = Vec::new();
+        self.stream_records(&mut |record| {
+            records.push(record);
+            Ok(())
+        })?;
+
+        Ok(records)
+    }
+
+    /// Maps every row in timestamp order and hands it to `f` as soon as it is read
+    fn stream_records(&self, f: &mut dyn FnMut(ExtractedRecord) -> Result<()>) -> Result<()> {
+        let conn = self.open_connection()?;
+        let query = self.records_query(&conn, false)?;
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            f(self.map_record(row)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches one channel's records in timestamp order
+    /// The connection and query are kept for the next channel, and the prepared statement is
+    /// taken from the connection's statement cache so only the channel id is re-bound
+    fn query_channel_records(&self, channel_id: &str) -> Result<Vec<ExtractedRecord>> {
+        let mut channel_fetch = self.channel_fetch.lock().map_err(|_| "channel fetch lock poisoned")?;
+        let fetch = match channel_fetch.take() {
+            Some(fetch) => fetch,
+            None => {
+                let conn = self.open_connection()?;
+                conn.set_prepared_statement_cache_capacity(self.statement_cache_capacity);
+                let query = self.records_query(&conn, true)?;
+                ChannelFetch { conn, query }
+            }
+        };
+        let fetch = channel_fetch.insert(fetch);
+        let mut stmt = fetch.conn.prepare_cached(&fetch.query)?;
         let records = stmt
-            .query_map([], |row: &Row| {
-                let raw_timestamp: String = row.get(2)?;
-                let formatted_timestamp = format_timestamp_to_local(&raw_timestamp);
-                
-                Ok(ExtractedRecord {
-                    channel_name: row.get(0)?,
-                    username: row.get(1)?,
-                    timestamp: formatted_timestamp,
-                    content: row.get(3)?,
-                })
-            })?
+            .query_map(params_from_iter([channel_id]), |row: &Row| self.map_record(row))?
             .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()?;
 
         Ok(records)
     }
+
+    /// Maps a row of the extraction query to a record
+    fn map_record(&self, row: &Row) -> rusqlite::Result<ExtractedRecord> {
+        let raw_timestamp: String = row.get(2)?;
+        let formatted_timestamp = format_timestamp_in(&raw_timestamp, self.timezone);
+
+        Ok(ExtractedRecord {
+            channel_name: row.get(0)?,
+            username: row.get(1)?,
+            timestamp: formatted_timestamp,
+            raw_timestamp,
+            content: row.get(3)?,
+            user_id: row.get(4)?,
+            message_id: row.get(5)?,
+            reply_to: row.get(6)?,
+            deleted: row.get(7)?,
+            is_thread: row.get(8)?,
+            attachments: row
+                .get::<_, Option<String>>(9)?
+                .map(|raw| parse_attachments(&raw))
+                .unwrap_or_default(),
+            channel_id: row.get(10)?,
+            channel_index: None,
+            category: row.get(11)?,
+            source: Some(self.db_path.clone()),
+            reactions: row
+                .get::<_, Option<String>>(12)?
+                .map(|raw| parse_reactions(&raw))
+                .unwrap_or_default(),
+            forwarded: row.get(13)?,
+            duplicate: false,
+            pinned: row.get(14)?,
+            language: None,
+            jump_url: row.get(15)?,
+        })
+    }
+}
+
+impl DataRepository for SqliteDataRepository {
+    fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+        self.query_records()
+    }
+
+    fn fetch_records_streaming(&self, f: &mut dyn FnMut(ExtractedRecord) -> Result<()>) -> Result<()> {
+        self.stream_records(f)
+    }
+
+    fn fetch_channel_ids(&self) -> Result<Vec<String>> {
+        let conn = self.open_connection()?;
+        let s = &self.schema;
+        let mut conditions = vec![format!("m.{} IS NOT NULL", s.message_channel_id)];
+        conditions.extend(top_channels_filter(s, self.top_channels));
+        let query = format!(
+            "SELECT DISTINCT CAST(m.{} AS TEXT) FROM {} m {} ORDER BY 1",
+            s.message_channel_id,
+            s.messages_table,
+            where_clause(&conditions)
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let ids = stmt
+            .query_map([], |row: &Row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, rusqlite::Error>>()?;
+
+        Ok(ids)
+    }
+
+    fn fetch_by_channel(&self, channel_id: &str) -> Result<Vec<ExtractedRecord>> {
+        self.query_channel_records(channel_id)
+    }
+
+    fn fetch_usernames(&self) -> Result<HashMap<String, String>> {
+        Ok(self.usernames()?.as_ref().clone())
+    }
+
+    fn fetch_channel_names(&self) -> Result<HashMap<String, String>> {
+        Ok(self.channel_names()?.as_ref().clone())
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::merge::MergingRepository;
+    use tempfile::TempDir;
+
+    /// Creates a fixture database with the standard schema and returns its path
+    fn create_fixture(dir: &TempDir) -> String {
+        let path = dir.path().join("fixture.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+            INSERT INTO channels VALUES (1, 'general', 'https://example.com/1');
+            INSERT INTO users VALUES ('u1', 'alice');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-16T10:30:00Z', 'second');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'first');
+            "#,
+        )
+        .unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_renamed_schema_extracts_like_the_default() {
+        // A shared-cache in-memory database lives as long as this connection stays open
+        let uri = "file:renamed_schema?mode=memory&cache=shared";
+        let conn = Connection::open_with_flags(uri, OpenFlags::default() | OpenFlags::SQLITE_OPEN_URI).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE chan (cid INTEGER PRIMARY KEY, title TEXT);
+            CREATE TABLE people (pid TEXT PRIMARY KEY, handle TEXT);
+            CREATE TABLE msg (chan_ref INTEGER, author_id TEXT, sent_at TEXT, body TEXT, message_id TEXT);
+            INSERT INTO chan VALUES (1, 'general'), (2, 'random');
+            INSERT INTO people VALUES ('u1', 'alice');
+            INSERT INTO msg VALUES (2, 'u1', '2025-12-16T10:30:00Z', 'second', 'm2');
+            INSERT INTO msg VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'first', 'm1');
+            INSERT INTO msg VALUES (1, 'u2', '2025-12-17T09:00:00Z', 'third', 'm3');
+            "#,
+        )
+        .unwrap();
+        let schema = SchemaConfig {
+            messages_table: "msg".to_string(),
+            channels_table: "chan".to_string(),
+            users_table: "people".to_string(),
+            message_channel_id: "chan_ref".to_string(),
+            message_user_id: "author_id".to_string(),
+            message_timestamp: "sent_at".to_string(),
+            message_content: "body".to_string(),
+            channel_id: "cid".to_string(),
+            channel_name: "title".to_string(),
+            user_id: "pid".to_string(),
+            username: "handle".to_string(),
+        };
+        let repository = SqliteDataRepository::new_with_schema(uri.to_string(), schema)
+            .unwrap()
+            .with_top_channels(1);
+
+        let records = repository.fetch_all_records().unwrap();
+        let rows: Vec<(&str, &str, &str)> = records
+            .iter()
+            .map(|r| (r.channel_name.as_str(), r.username.as_str(), r.content.as_str()))
+            .collect();
+        assert_eq!(rows, vec![("general", "alice", "first"), ("general", "Unknown", "third")]);
+        assert_eq!(records[0].message_id.as_deref(), Some("m1"));
+        assert_eq!(repository.fetch_channel_ids().unwrap(), vec!["1"]);
+        assert_eq!(repository.fetch_by_channel("1").unwrap(), records);
+        assert_eq!(repository.fetch_usernames().unwrap()["u1"], "alice");
+        assert_eq!(repository.fetch_channel_names().unwrap()["2"], "random");
+        let report = repository.validate().unwrap();
+        assert!(report.missing.is_empty(), "{:?}", report.missing);
+        assert_eq!(report.message_count, 3);
+    }
+
+    #[test]
+    fn test_renamed_schema_reports_missing_names_as_configured() {
+        let dir = TempDir::new().unwrap();
+        let path = create_fixture(&dir);
+        let schema = SchemaConfig {
+            messages_table: "msg".to_string(),
+            ..Default::default()
+        };
+        let repository = SqliteDataRepository::new_with_schema(path.clone(), schema).unwrap();
+
+        assert_eq!(repository.validate().unwrap().missing, vec!["msg"]);
+        let bad = SchemaConfig {
+            username: "name; --".to_string(),
+            ..Default::default()
+        };
+        assert!(SqliteDataRepository::new_with_schema(path, bad).is_err());
+    }
+
+    #[test]
+    fn test_fetch_all_records_ordered_by_timestamp() {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteDataRepository::new(create_fixture(&dir));
+
+        let records = repository.fetch_all_records().unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].channel_name, "general");
+        assert_eq!(records[0].channel_id.as_deref(), Some("1"));
+        assert_eq!(records[0].username, "alice");
+        assert_eq!(records[0].user_id.as_deref(), Some("u1"));
+        assert_eq!(records[0].content, "first");
+        assert_eq!(records[0].raw_timestamp, "2025-12-15T09:00:00Z");
+        assert_eq!(records[1].content, "second");
+        assert_eq!(records[0].message_id, None);
+        assert_eq!(records[0].reply_to, None);
+        assert!(!records[0].deleted);
+        assert!(!records[0].is_thread);
+    }
+
+    #[test]
+    fn test_merged_records_carry_their_source_path() {
+        let first_dir = TempDir::new().unwrap();
+        let second_dir = TempDir::new().unwrap();
+        let first_path = create_fixture(&first_dir);
+        let second_path = create_fixture(&second_dir);
+        Connection::open(&second_path)
+            .unwrap()
+            .execute("INSERT INTO messages VALUES (1, 'u1', '2025-12-15T12:00:00Z', 'from second')", [])
+            .unwrap();
+        let repository = MergingRepository::new(vec![
+            Box::new(SqliteDataRepository::new(first_path.clone())),
+            Box::new(SqliteDataRepository::new(second_path.clone())),
+        ]);
+
+        let records = repository.fetch_all_records().unwrap();
+
+        assert_eq!(records.len(), 5);
+        let middle = records.iter().find(|record| record.content == "from second").unwrap();
+        assert_eq!(middle.source.as_deref(), Some(second_path.as_str()));
+        assert_eq!(records[2].content, "from second");
+        assert_eq!(records.iter().filter(|r| r.source.as_deref() == Some(first_path.as_str())).count(), 2);
+    }
+
+    #[test]
+    fn test_streaming_visits_each_row_once_in_timestamp_order() {
+        let dir = TempDir::new().unwrap();
+        let path = create_fixture(&dir);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("INSERT INTO messages VALUES (1, 'u1', '2025-12-15T12:00:00Z', 'middle');")
+            .unwrap();
+        let repository = SqliteDataRepository::new(path);
+
+        let mut streamed = Vec::new();
+        repository
+            .fetch_records_streaming(&mut |record| {
+                streamed.push(record.content);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(streamed, vec!["first", "middle", "second"]);
+        let contents: Vec<String> = repository.fetch_all_records().unwrap().into_iter().map(|r| r.content).collect();
+        assert_eq!(streamed, contents);
+    }
+
+    #[test]
+    fn test_streaming_stops_at_the_first_callback_error() {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteDataRepository::new(create_fixture(&dir));
+
+        let mut calls = 0;
+        let result = repository.fetch_records_streaming(&mut |_| {
+            calls += 1;
+            Err("writer full".into())
+        });
+
+        assert_eq!(result.unwrap_err().to_string(), "writer full");
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_fetch_by_channel_matches_fetch_all() {
+        let dir = TempDir::new().unwrap();
+        let path = create_fixture(&dir);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                r#"
+                INSERT INTO channels VALUES (2, 'random', 'https://example.com/2');
+                INSERT INTO messages VALUES (2, 'u1', '2025-12-15T10:00:00Z', 'between');
+                INSERT INTO messages VALUES (2, 'u1', '2025-12-17T10:00:00Z', 'last');
+                "#,
+            )
+            .unwrap();
+        let repository = SqliteDataRepository::new(path);
+
+        let all = repository.fetch_all_records().unwrap();
+        let ids = repository.fetch_channel_ids().unwrap();
+        let per_channel: Vec<ExtractedRecord> = ids
+            .iter()
+            .flat_map(|id| repository.fetch_by_channel(id).unwrap())
+            .collect();
+
+        assert_eq!(ids, vec!["1", "2"]);
+        let mut expected = all.clone();
+        expected.sort_by_key(|record| record.channel_id.clone());
+        assert_eq!(per_channel, expected);
+        assert_eq!(all.len(), 4);
+    }
+
+    #[test]
+    fn test_reused_channel_statement_matches_re_prepared() {
+        let dir = TempDir::new().unwrap();
+        let path = create_fixture(&dir);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                r#"
+                INSERT INTO channels VALUES (2, 'random', 'https://example.com/2');
+                INSERT INTO messages VALUES (2, 'u1', '2025-12-15T10:00:00Z', 'between');
+                "#,
+            )
+            .unwrap();
+        let fetch_twice = |repository: SqliteDataRepository| -> Vec<ExtractedRecord> {
+            let ids = repository.fetch_channel_ids().unwrap();
+            ids.iter()
+                .chain(&ids)
+                .flat_map(|id| repository.fetch_by_channel(id).unwrap())
+                .collect()
+        };
+
+        let reused = fetch_twice(SqliteDataRepository::new(path.clone()));
+        let re_prepared = fetch_twice(SqliteDataRepository::new(path).with_statement_cache_capacity(0));
+
+        assert_eq!(reused, re_prepared);
+        let contents: Vec<&str> = reused.iter().map(|record| record.content.as_str()).collect();
+        assert_eq!(contents, vec!["first", "second", "between", "first", "second", "between"]);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_timestamps_are_formatted_in_the_configured_timezone() {
+        let dir = TempDir::new().unwrap();
+        let path = create_fixture(&dir);
+        let timestamps = |timezone: &str| -> Vec<String> {
+            let repository = SqliteDataRepository::new(path.clone()).with_timezone(timezone.parse().unwrap());
+            repository.fetch_all_records().unwrap().into_iter().map(|record| record.timestamp).collect()
+        };
+
+        assert_eq!(timestamps("UTC"), vec!["2025-12-15 09:00:00 +00:00", "2025-12-16 10:30:00 +00:00"]);
+        assert_eq!(
+            timestamps("America/New_York"),
+            vec!["2025-12-15 04:00:00 -05:00", "2025-12-16 05:30:00 -05:00"]
+        );
+    }
+
+    #[test]
+    fn test_validate_good_fixture() {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteDataRepository::new(create_fixture(&dir));
+
+        let report = repository.validate().unwrap();
+
+        assert!(report.is_ok());
+        assert_eq!(report.message_count, 2);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_validate_reports_channel_ranges() {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteDataRepository::new(create_fixture(&dir));
+
+        let report = repository.validate().unwrap();
+
+        let general = &report.channel_ranges["general"];
+        assert_eq!((general.first.as_str(), general.last.as_str()), ("2025-12-15T09:00:00Z", "2025-12-16T10:30:00Z"));
+        assert_eq!(general.span_days, 1);
+    }
+
+    #[test]
+    fn test_validate_reports_missing_table_and_bad_timestamps() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("broken.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, url TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'ok');
+            INSERT INTO messages VALUES (1, 'u1', 'yesterday-ish', 'bad');
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let report = repository.validate().unwrap();
+
+        assert!(!report.is_ok());
+        assert_eq!(report.missing, vec!["channels.name", "users"]);
+        assert_eq!(report.message_count, 2);
+        assert_eq!(report.unparseable_timestamps, 1);
+    }
+
+    #[test]
+    fn test_validate_json_lists_expected_and_found_columns() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("no-content.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT);
+            CREATE TABLE users (user_id TEXT, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT);
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let json: serde_json::Value = serde_json::from_str(&repository.validate().unwrap().to_json().unwrap()).unwrap();
+
+        assert_eq!(json["missing"], serde_json::json!(["messages.content"]));
+        assert_eq!(
+            json["tables"][2],
+            serde_json::json!({
+                "table": "messages",
+                "found": true,
+                "expected_columns": ["channel_id", "user_id", "timestamp", "content"],
+                "found_columns": {"channel_id": "INTEGER", "timestamp": "TEXT", "user_id": "TEXT"},
+                "missing_columns": ["content"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_fetch_all_records_from_uri_path() {
+        let dir = TempDir::new().unwrap();
+        let uri = format!("file:{}?mode=ro&immutable=1", create_fixture(&dir));
+        let repository = SqliteDataRepository::new(uri);
+
+        let records = repository.fetch_all_records().unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].content, "first");
+    }
+
+    #[test]
+    fn test_top_channels_keeps_busiest_channels() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("busy.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+            INSERT INTO channels VALUES (1, 'quiet', ''), (2, 'busy', ''), (3, 'medium', ''), (4, 'also-medium', '');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'q');
+            INSERT INTO messages VALUES (2, 'u1', '2025-12-15T09:01:00Z', 'b1');
+            INSERT INTO messages VALUES (2, 'u1', '2025-12-15T09:02:00Z', 'b2');
+            INSERT INTO messages VALUES (2, 'u1', '2025-12-15T09:03:00Z', 'b3');
+            INSERT INTO messages VALUES (3, 'u1', '2025-12-15T09:04:00Z', 'm1');
+            INSERT INTO messages VALUES (3, 'u1', '2025-12-15T09:05:00Z', 'm2');
+            INSERT INTO messages VALUES (4, 'u1', '2025-12-15T09:06:00Z', 'a1');
+            INSERT INTO messages VALUES (4, 'u1', '2025-12-15T09:07:00Z', 'a2');
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned()).with_top_channels(2);
+
+        let records = repository.fetch_all_records().unwrap();
+
+        // 'also-medium' wins the tie with 'medium' by name
+        let channels: HashSet<&str> = records.iter().map(|r| r.channel_name.as_str()).collect();
+        assert_eq!(channels, HashSet::from(["busy", "also-medium"]));
+        assert_eq!(records.len(), 5);
+    }
+
+    #[test]
+    fn test_fetch_all_records_reads_deleted_flag() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("deleted.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT, deleted INTEGER);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'kept', 0);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:05:00Z', '', 1);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:10:00Z', 'legacy', NULL);
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let records = repository.fetch_all_records().unwrap();
+
+        let flags: Vec<bool> = records.iter().map(|r| r.deleted).collect();
+        assert_eq!(flags, vec![false, true, false]);
+    }
+
+    #[test]
+    fn test_fetch_all_records_reads_attachments_column() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("attachments.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT, attachments TEXT);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'plain', '');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:05:00Z', 'crawled', 'Yes');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:10:00Z', 'legacy', NULL);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:15:00Z', 'pair', 'https://cdn/a.png,https://cdn/b.png');
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let records = repository.fetch_all_records().unwrap();
+
+        let counts: Vec<usize> = records.iter().map(|r| r.attachments.len()).collect();
+        assert_eq!(counts, vec![0, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_fetch_all_records_reads_reactions_column() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("reactions.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT, reactions TEXT);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'liked', '[{"emoji":"👍","count":2},{"emoji":"🔥","count":1}]');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:05:00Z', 'ignored', NULL);
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let records = repository.fetch_all_records().unwrap();
+
+        let emoji: Vec<(&str, u64)> = records[0].reactions.iter().map(|r| (r.emoji.as_str(), r.count)).collect();
+        assert_eq!(emoji, vec![("👍", 2), ("🔥", 1)]);
+        assert!(records[1].reactions.is_empty());
+    }
+
+    #[test]
+    fn test_fetch_all_records_flags_thread_channels() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("threads.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT, parent_id INTEGER);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+            INSERT INTO channels VALUES (1, 'general', '', NULL), (2, 'release-thread', '', 1);
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-15T09:00:00Z', 'main');
+            INSERT INTO messages VALUES (2, 'u1', '2025-12-15T09:05:00Z', 'in thread');
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let records = repository.fetch_all_records().unwrap();
+
+        let flags: Vec<(&str, bool)> = records.iter().map(|r| (r.content.as_str(), r.is_thread)).collect();
+        assert_eq!(flags, vec![("main", false), ("in thread", true)]);
+    }
+
+    #[test]
+    fn test_fetch_all_records_reads_optional_reply_columns() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("replies.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, message_id TEXT, user_id TEXT, timestamp TEXT, content TEXT, reply_to TEXT);
+            INSERT INTO channels VALUES (1, 'general', 'https://example.com/1');
+            INSERT INTO messages VALUES (1, 'm1', 'u1', '2025-12-15T09:00:00Z', 'question', NULL);
+            INSERT INTO messages VALUES (1, 'm2', 'u1', '2025-12-15T09:05:00Z', 'answer', 'm1');
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let records = repository.fetch_all_records().unwrap();
+
+        assert_eq!(records[0].message_id.as_deref(), Some("m1"));
+        assert_eq!(records[0].reply_to, None);
+        assert_eq!(records[1].reply_to.as_deref(), Some("m1"));
+        assert!(records[0].attachments.is_empty());
+        assert_eq!(records[1].jump_url.as_deref(), Some("https://example.com/1/m2"));
+    }
+
+    #[test]
+    fn test_jump_url_needs_channel_url_and_message_id() {
+        let dir = TempDir::new().unwrap();
+        // The plain fixture has channel URLs but no message ids
+        let records = SqliteDataRepository::new(create_fixture(&dir)).fetch_all_records().unwrap();
+
+        assert!(records.iter().all(|record| record.jump_url.is_none()));
+    }
+
+    #[test]
+    fn test_unresolved_mentions_missing_from_users_table() {
+        use core::domain::ExtractionReport;
+        use core::transforms::{RecordTransform, ResolveMentions};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mentions.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT, url TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+            INSERT INTO users VALUES ('123', 'alice');
+            INSERT INTO messages VALUES (1, '123', '2025-12-15T09:00:00Z', 'hey <@123>, ask <@456>');
+            "#,
+        )
+        .unwrap();
+        let repository = SqliteDataRepository::new(path.to_string_lossy().into_owned());
+
+        let transform = ResolveMentions::new(repository.fetch_usernames().unwrap());
+        let mut records = repository.fetch_all_records().unwrap();
+        transform.apply(&mut records[0]);
+        let mut report = ExtractionReport::default();
+        transform.report(&mut report);
+
+        assert_eq!(records[0].content, "hey @alice, ask <@456>");
+        assert!(report.unresolved_mentions.contains("456"));
+        assert!(!report.unresolved_mentions.contains("123"));
+    }
+
+    #[test]
+    fn test_name_maps_are_queried_once_and_shared_by_transforms() {
+        use core::transforms::{RecordTransform, ResolveChannelMentions, ResolveMentions};
+
+        let dir = TempDir::new().unwrap();
+        let path = create_fixture(&dir);
+        Connection::open(&path).unwrap().execute("INSERT INTO users VALUES ('123', 'bob')", []).unwrap();
+        let repository = SqliteDataRepository::new(path.clone());
+
+        let usernames = repository.usernames().unwrap();
+        let channel_names = repository.channel_names().unwrap();
+        // Once built, the maps no longer depend on the database
+        Connection::open(&path).unwrap().execute_batch("DROP TABLE users; DROP TABLE channels;").unwrap();
+        assert!(Arc::ptr_eq(&usernames, &repository.usernames().unwrap()));
+        assert!(Arc::ptr_eq(&channel_names, &repository.channel_names().unwrap()));
+        assert_eq!(repository.fetch_usernames().unwrap()["u1"], "alice");
+
+        let resolve_users = ResolveMentions::new(repository.usernames().unwrap());
+        let resolve_channels = ResolveChannelMentions::new(repository.channel_names().unwrap());
+        let mut record = ExtractedRecord {
+            content: "<@123> see <#1> and <#9>".to_string(),
+            ..Default::default()
+        };
+        resolve_users.apply(&mut record);
+        resolve_channels.apply(&mut record);
+
+        assert_eq!(record.content, "@bob see #general and <#9>");
+        assert_eq!(Arc::strong_count(&usernames), 3);
+    }
+
+    #[cfg(not(feature = "timestamps"))]
+    #[test]
+    fn test_fetch_all_records_passes_timestamps_through_without_feature() {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteDataRepository::new(create_fixture(&dir));
+
+        let records = repository.fetch_all_records().unwrap();
+
+        assert_eq!(records[0].timestamp, "2025-12-15T09:00:00Z");
+    }
+}
@@ -0,0 +1,122 @@
+use core::ports::Result;
+use serde::Deserialize;
+
+/// Table and column names of the source database, for archives that don't use the default
+/// `messages`/`channels`/`users` schema. Every name is interpolated into SQL, so `validate`
+/// only accepts plain identifiers. Optional columns (`message_id`, `reply_to`, ...) keep their
+/// default names
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaConfig {
+    pub messages_table: String,
+    pub channels_table: String,
+    pub users_table: String,
+    /// `messages` column referencing the channel's id
+    pub message_channel_id: String,
+    /// `messages` column referencing the author's user id
+    pub message_user_id: String,
+    pub message_timestamp: String,
+    pub message_content: String,
+    /// `channels` primary key
+    pub channel_id: String,
+    pub channel_name: String,
+    /// `users` primary key
+    pub user_id: String,
+    pub username: String,
+}
+
+impl Default for SchemaConfig {
+    fn default() -> Self {
+        Self {
+            messages_table: "messages".to_string(),
+            channels_table: "channels".to_string(),
+            users_table: "users".to_string(),
+            message_channel_id: "channel_id".to_string(),
+            message_user_id: "user_id".to_string(),
+            message_timestamp: "timestamp".to_string(),
+            message_content: "content".to_string(),
+            channel_id: "id".to_string(),
+            channel_name: "name".to_string(),
+            user_id: "user_id".to_string(),
+            username: "username".to_string(),
+        }
+    }
+}
+
+impl SchemaConfig {
+    /// Parses a JSON object overriding some of the default names, e.g.
+    /// `{"messages_table": "msg", "message_user_id": "author_id"}`, and validates the result
+    pub fn from_json(json: &str) -> Result<Self> {
+        let schema: Self = serde_json::from_str(json)?;
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Rejects any name that is not a plain SQL identifier (ASCII letters, digits and `_`,
+    /// not starting with a digit), so names can be interpolated into queries safely
+    pub fn validate(&self) -> Result<()> {
+        let names = [
+            ("messages_table", &self.messages_table),
+            ("channels_table", &self.channels_table),
+            ("users_table", &self.users_table),
+            ("message_channel_id", &self.message_channel_id),
+            ("message_user_id", &self.message_user_id),
+            ("message_timestamp", &self.message_timestamp),
+            ("message_content", &self.message_content),
+            ("channel_id", &self.channel_id),
+            ("channel_name", &self.channel_name),
+            ("user_id", &self.user_id),
+            ("username", &self.username),
+        ];
+        match names.iter().find(|(_, name)| !is_identifier(name)) {
+            Some((field, name)) => Err(format!("schema {} '{}' is not a plain identifier", field, name).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Tables and columns the extraction query cannot do without
+    pub(crate) fn required(&self) -> Vec<(&str, Vec<&str>)> {
+        vec![
+            (&self.channels_table, vec![&self.channel_id, &self.channel_name]),
+            (&self.users_table, vec![&self.user_id, &self.username]),
+            (
+                &self.messages_table,
+                vec![&self.message_channel_id, &self.message_user_id, &self.message_timestamp, &self.message_content],
+            ),
+        ]
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json_overrides_some_names() {
+        let schema = SchemaConfig::from_json(r#"{"messages_table": "msg", "message_user_id": "author_id"}"#).unwrap();
+
+        assert_eq!(schema.messages_table, "msg");
+        assert_eq!(schema.message_user_id, "author_id");
+        assert_eq!(schema.channels_table, "channels");
+    }
+
+    #[test]
+    fn test_names_must_be_plain_identifiers() {
+        for name in ["messages; DROP TABLE users", "", "1msg", "m\"x", "naïve"] {
+            let schema = SchemaConfig {
+                messages_table: name.to_string(),
+                ..Default::default()
+            };
+            assert!(schema.validate().is_err(), "{:?} accepted", name);
+        }
+        assert!(SchemaConfig::default().validate().is_ok());
+        assert!(SchemaConfig::from_json(r#"{"users_table": "x y"}"#).is_err());
+        assert!(SchemaConfig::from_json(r#"{"user_table": "people"}"#).is_err());
+    }
+}
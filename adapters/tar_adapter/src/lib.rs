@@ -0,0 +1,113 @@
+use core::ports::{OutputSink, Result};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use tar::{Builder, Header};
+
+/// Streams every written file as an entry of a tar archive
+/// Writers hand over complete file contents, which gives the entry size up front,
+/// so the archive can be written to a non-seekable stream such as stdout
+pub struct TarSink<W: Write + Send> {
+    builder: Mutex<Option<Builder<W>>>,
+}
+
+impl<W: Write + Send> TarSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            builder: Mutex::new(Some(Builder::new(writer))),
+        }
+    }
+
+    /// Writes the archive trailer and returns the underlying writer
+    pub fn finish(&self) -> Result<W> {
+        let builder = self
+            .builder
+            .lock()
+            .map_err(|_| "tar archive lock poisoned")?
+            .take()
+            .ok_or("tar archive already finished")?;
+        let mut writer = builder.into_inner()?;
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write + Send> OutputSink for TarSink<W> {
+    fn write_file(&self, relative_path: &Path, contents: &[u8]) -> Result<()> {
+        let mut guard = self.builder.lock().map_err(|_| "tar archive lock poisoned")?;
+        let builder = guard.as_mut().ok_or("tar archive already finished")?;
+
+        // Fixed metadata keeps archives reproducible across runs
+        let mut header = Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        builder.append_data(&mut header, relative_path, contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::domain::ExtractedRecord;
+    use core::ports::MarkdownWriter;
+    use markdown_adapter::{MarkdownOptions, MarkdownWriterAdapter};
+    use std::io::Read;
+    use std::sync::Arc;
+    use tar::Archive;
+
+    fn record(channel: &str, timestamp: &str, content: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: channel.to_string(),
+            username: "alice".to_string(),
+            timestamp: timestamp.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tar_stream_contains_markdown_files() {
+        let sink = Arc::new(TarSink::new(Vec::new()));
+        let writer = MarkdownWriterAdapter::with_sink(sink.clone(), MarkdownOptions::default());
+        let records = vec![
+            record("general", "2025-11-15 12:00:00", "november"),
+            record("general", "2025-12-15 12:00:00", "december"),
+            record("random", "2025-12-15 12:00:00", "elsewhere"),
+        ];
+
+        writer.write(&records).unwrap();
+        let bytes = sink.finish().unwrap();
+
+        let mut archive = Archive::new(bytes.as_slice());
+        let mut entries: Vec<(String, String)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (name, content)
+            })
+            .collect();
+        entries.sort();
+
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["general-2025-11.md", "general-2025-12.md", "random-2025-12.md"]
+        );
+        assert!(entries[1].1.starts_with("# #general\n\n*1 messages*"));
+        assert!(entries[1].1.contains("december"));
+    }
+
+    #[test]
+    fn test_finish_twice_errors() {
+        let sink = TarSink::new(Vec::new());
+        sink.finish().unwrap();
+        assert!(sink.finish().is_err());
+        assert!(sink.write_file(Path::new("late.md"), b"late").is_err());
+    }
+}
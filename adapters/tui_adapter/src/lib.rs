@@ -0,0 +1,80 @@
+use core::ports::{DataRepository, Result};
+use core::utils::OutputTimezone;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+pub mod state;
+
+pub use state::BrowseState;
+
+/// Lines scrolled by PageUp / PageDown in the message pane
+const PAGE_LINES: u16 = 10;
+
+/// Fetches all records and opens an interactive browser until the user quits with `q`
+/// Up/Down switch channel-month, PageUp/PageDown scroll its messages; months are taken in `timezone`
+pub fn browse(repository: &dyn DataRepository, timezone: OutputTimezone) -> Result<()> {
+    let state = BrowseState::new_in(repository.fetch_all_records()?, timezone);
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, state);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, mut state: BrowseState) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, &state))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+            KeyCode::PageDown | KeyCode::Char(' ') => state.scroll_down(PAGE_LINES),
+            KeyCode::PageUp => state.scroll_up(PAGE_LINES),
+            _ => {}
+        }
+    }
+}
+
+/// Renders the channel-month list on the left and the selected messages on the right
+fn draw(frame: &mut Frame, state: &BrowseState) {
+    let [list_area, messages_area] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(frame.area());
+
+    let list = List::new(state.groups.iter().map(|group| group.label()))
+        .block(Block::default().borders(Borders::ALL).title("Channels (q to quit)"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut list_state = ListState::default().with_selected(state.selected_group().map(|_| state.selected));
+    frame.render_stateful_widget(list, list_area, &mut list_state);
+
+    let (title, lines) = match state.selected_group() {
+        Some(group) => {
+            let mut lines = Vec::new();
+            for record in &group.records {
+                lines.push(Line::from(vec![
+                    Span::styled(record.username.clone(), Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("  {}", record.timestamp)),
+                ]));
+                lines.extend(record.content.lines().map(|line| Line::from(line.to_string())));
+                lines.push(Line::default());
+            }
+            (format!("#{} {}", group.channel, group.month), lines)
+        }
+        None => ("Messages".to_string(), vec![Line::from("No messages")]),
+    };
+    let messages = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((state.scroll, 0));
+    frame.render_widget(messages, messages_area);
+}
@@ -0,0 +1,143 @@
+use core::domain::ExtractedRecord;
+use core::utils::{extract_year_month_in, OutputTimezone};
+use std::collections::BTreeMap;
+
+/// Messages of one channel in one month, as listed in the browser
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMonth {
+    pub channel: String,
+    pub month: String,
+    pub records: Vec<ExtractedRecord>,
+}
+
+impl ChannelMonth {
+    /// Label shown in the channel list, e.g. `#general 2025-12 (42)`
+    pub fn label(&self) -> String {
+        format!("#{} {} ({})", self.channel, self.month, self.records.len())
+    }
+}
+
+/// Navigation state of the browser: the channel-month list and the selected entry
+/// Kept free of terminal types so it can be tested without a TTY
+#[derive(Debug, Clone, Default)]
+pub struct BrowseState {
+    pub groups: Vec<ChannelMonth>,
+    pub selected: usize,
+    /// Line offset of the message pane
+    pub scroll: u16,
+}
+
+impl BrowseState {
+    /// Groups records by channel and month like the Markdown writer, sorted by channel then month
+    pub fn new(records: Vec<ExtractedRecord>) -> Self {
+        Self::new_in(records, OutputTimezone::Local)
+    }
+
+    /// Like `new`, with months taken in the given timezone
+    pub fn new_in(records: Vec<ExtractedRecord>, timezone: OutputTimezone) -> Self {
+        let mut grouped: BTreeMap<(String, String), Vec<ExtractedRecord>> = BTreeMap::new();
+        for record in records {
+            let month = extract_year_month_in(&record.timestamp, timezone).unwrap_or_else(|| "unknown".to_string());
+            grouped
+                .entry((record.channel_name.clone(), month))
+                .or_default()
+                .push(record);
+        }
+
+        let groups = grouped
+            .into_iter()
+            .map(|((channel, month), records)| ChannelMonth { channel, month, records })
+            .collect();
+        Self {
+            groups,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    /// The channel-month currently shown, if there is any
+    pub fn selected_group(&self) -> Option<&ChannelMonth> {
+        self.groups.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.groups.len() {
+            self.selected += 1;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_add(lines);
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(channel: &str, timestamp: &str, content: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: channel.to_string(),
+            username: "alice".to_string(),
+            timestamp: timestamp.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn fixture() -> BrowseState {
+        BrowseState::new(vec![
+            record("random", "2025-12-15 12:00:00", "elsewhere"),
+            record("general", "2025-11-15 12:00:00", "november"),
+            record("general", "2025-12-15 12:00:00", "december"),
+            record("general", "2025-12-16 12:00:00", "later"),
+        ])
+    }
+
+    #[test]
+    fn test_state_initializes_channel_list_and_selection() {
+        let state = fixture();
+
+        let labels: Vec<String> = state.groups.iter().map(ChannelMonth::label).collect();
+        assert_eq!(
+            labels,
+            vec!["#general 2025-11 (1)", "#general 2025-12 (2)", "#random 2025-12 (1)"]
+        );
+        assert_eq!(state.selected, 0);
+        assert_eq!(state.selected_group().unwrap().records[0].content, "november");
+    }
+
+    #[test]
+    fn test_selection_stays_in_bounds_and_resets_scroll() {
+        let mut state = fixture();
+
+        state.select_previous();
+        assert_eq!(state.selected, 0);
+        state.scroll_down(5);
+        state.select_next();
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected, 2);
+        assert_eq!(state.scroll, 0);
+        assert_eq!(state.selected_group().unwrap().channel, "random");
+    }
+
+    #[test]
+    fn test_empty_state_has_no_selection() {
+        let mut state = BrowseState::new(Vec::new());
+        state.select_next();
+        assert!(state.selected_group().is_none());
+    }
+}
@@ -1,44 +1,1121 @@
-use clap::Parser;
-use core::ports::{DataRepository, MarkdownWriter};
-use sqlite_adapter::SqliteDataRepository;
-use markdown_adapter::MarkdownWriterAdapter;
+use clap::{ArgGroup, Parser, Subcommand};
+use core::application::{ChannelFilter, ExtractionOptions, ExtractionServiceImpl, NullTimestampPolicy, ThreadFilter};
+use core::logging::{LogFormat, StderrLogger};
+use core::last_run::LastRun;
+use core::merge::MergingRepository;
+use core::transforms::{
+    FlattenCodeBlocks, MergeIdentities, Redact, ResolveChannelMentions, ResolveMentions, SanitizeContent, StripMentions, TransformedWriter, EMAIL_PATTERN,
+    PHONE_PATTERN, TOKEN_PATTERN,
+};
+use core::domain::ExtractedRecord;
+use core::ports::{DataRepository, MarkdownWriter, OutputSink};
+use core::registry::Registry;
+use core::summary::{build_summary, format_channel_ranges};
+use core::sinks::{DirectorySink, MeasuringSink, PrefixedSink, StreamSink};
+use core::utils::{format_bytes, parse_day, resolve_generated_at, Granularity, OutputTimezone};
+use sqlite_adapter::{SchemaConfig, SqliteDataRepository};
+use html_adapter::{clean_html_pages, HtmlTemplate, HtmlWriter};
+use csv_adapter::CsvWriter;
+use json_adapter::{ChannelsJsonWriter, JsonDataRepository, JsonRecordsWriter, SummaryJsonWriter};
+use markdown_adapter::{
+    channel_tag, check_output_file, prepare_output_folder, ChannelSort, ExistingOutput, Grouping, MarkdownOptions, MarkdownWriterAdapter,
+    LinksWriter, ReactionsCsvWriter, RosterWriter,
+};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tar_adapter::TarSink;
+
+type TarOutput = Box<dyn Write + Send>;
+
+/// Prints the first and last message of each channel to stderr, keeping stdout free for output
+struct StatsWriter;
+
+impl MarkdownWriter for StatsWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> core::ports::Result<()> {
+        eprint!("{}", format_channel_ranges(&build_summary(records).channel_ranges));
+        Ok(())
+    }
+}
+
+/// Hands records to several writers in turn, e.g. one Markdown writer per granularity
+struct WriterSet(Vec<Box<dyn MarkdownWriter>>);
+
+impl MarkdownWriter for WriterSet {
+    fn write(&self, records: &[ExtractedRecord]) -> core::ports::Result<()> {
+        for writer in &self.0 {
+            writer.write(records)?;
+        }
+        Ok(())
+    }
+}
 
 /// CLI tool to extract Discord message data from SQLite and format it as Markdown
 #[derive(Parser, Debug)]
 #[command(name = "discord-extractor")]
 #[command(about = "Extracts Discord messages from SQLite database and formats them as Markdown")]
+#[command(subcommand_negates_reqs = true)]
+#[command(group(ArgGroup::new("html_output").args(["html", "out_html"]).multiple(true)))]
 struct Cli {
-    /// Path to the source SQLite database file
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the source SQLite database file, or a `file:` URI with SQLite query parameters
+    /// (a JSON records file with `--input-format json`). Repeat to merge several sources into one export
     #[arg(short = 'i', long = "input-db", required = true)]
-    input_db: String,
+    input_db: Vec<String>,
+
+    /// Format of the input: sqlite, or json (an array of records as written by --records-json)
+    #[arg(long = "input-format", value_name = "FORMAT", default_value = "sqlite")]
+    input_format: String,
+
+    /// JSON file renaming the SQLite tables and columns for archives with a different schema,
+    /// e.g. `{"messages_table": "msg", "message_user_id": "author_id"}`
+    #[arg(long = "schema", value_name = "PATH")]
+    schema: Option<PathBuf>,
+
+    /// Format written to the output folder: markdown (the default), json (`channels.json`, messages
+    /// keyed by channel), csv (`messages.csv`, one row per message), or another registered writer
+    #[arg(long = "output-format", visible_alias = "format", value_name = "FORMAT", default_value = "markdown")]
+    output_format: String,
 
     /// Path to the output folder where Markdown files will be written
-    #[arg(short = 'o', long = "output-folder", required = true)]
-    output_folder: String,
+    /// (`-` writes them to stdout instead, each preceded by a `==> name <==` line).
+    /// `--output-file` is a deprecated alias
+    #[arg(
+        short = 'o',
+        long = "output-folder",
+        visible_aliases = ["output", "output-dir", "out-markdown"],
+        alias = "output-file",
+        required_unless_present_any = ["tar", "single_file"]
+    )]
+    output_folder: Option<String>,
+
+    /// Write all Markdown into this one file instead of an output folder, with a `# #channel`
+    /// section per channel and a `## <period>` section per period (`-` writes it to stdout)
+    #[arg(
+        long = "single-file",
+        value_name = "PATH",
+        conflicts_with_all = [
+            "output_folder", "by_weekday", "one_file_per_message", "obsidian", "index", "nav_footer",
+            "max_file_bytes", "html"
+        ]
+    )]
+    single_file: Option<String>,
+
+    /// Delete previously generated Markdown files from the output folder before writing
+    /// (unrelated files are kept)
+    #[arg(long = "clean", conflicts_with_all = ["overwrite", "fail_on_nonempty"])]
+    clean: bool,
+
+    /// Write into a non-empty output folder, replacing matching files and keeping stale ones
+    #[arg(long = "overwrite", conflicts_with = "fail_on_nonempty")]
+    overwrite: bool,
+
+    /// Resume an interrupted export: write into the existing output folder, skipping files
+    /// whose contents would not change
+    #[arg(long = "resume", conflicts_with_all = ["clean", "fail_on_nonempty"])]
+    resume: bool,
+
+    /// Add new messages to the channel files of an earlier export instead of replacing them,
+    /// skipping messages already in a file and updating its message count
+    #[arg(
+        long = "append",
+        conflicts_with_all = [
+            "clean", "fail_on_nonempty", "single_file", "by_weekday", "one_file_per_message", "index",
+            "max_file_bytes", "tar"
+        ]
+    )]
+    append: bool,
+
+    /// Flush every written file (and its directory) to disk before moving on, for archival
+    /// runs that must survive a crash; slower
+    #[arg(long = "fsync")]
+    fsync: bool,
+
+    /// Refuse to write into a non-empty output folder (the default)
+    #[arg(long = "fail-on-nonempty")]
+    fail_on_nonempty: bool,
+
+    /// Format everything in memory and print the size of each output file instead of writing
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+
+    /// Write all output files into a tar archive at this path instead (`-` streams to stdout)
+    #[arg(long = "tar", value_name = "PATH")]
+    tar: Option<String>,
+
+    /// Only export the N channels with the most messages (ties broken by channel name; SQLite input only)
+    #[arg(long = "top-channels", visible_alias = "limit-channels", value_name = "N")]
+    top_channels: Option<usize>,
+
+    /// Format of progress logs written to stderr: text or json (newline-delimited objects)
+    #[arg(long = "log-format", value_name = "FORMAT", default_value = "text")]
+    log_format: LogFormat,
+
+    /// Also log per-file progress
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+
+    /// Number of ancestor messages to quote above each reply (0 disables quoting)
+    #[arg(long = "reply-depth", default_value_t = 0)]
+    reply_depth: usize,
+
+    /// Group messages into one file per weekday (`monday.md` ... `sunday.md`) instead of per channel-month
+    #[arg(long = "by-weekday")]
+    by_weekday: bool,
+
+    /// Write each message to its own file: `<channel>/<YYYY-MM-DD>/<message-id>.md`
+    #[arg(long = "one-file-per-message", conflicts_with = "by_weekday")]
+    one_file_per_message: bool,
+
+    /// Abort instead of writing more than this many Markdown files
+    #[arg(long = "max-files", value_name = "N")]
+    max_files: Option<usize>,
+
+    /// Roll Markdown files over into `<name>.part2.md`, ... once they would exceed this many bytes
+    /// Files are only split between messages
+    #[arg(long = "max-file-bytes", value_name = "N")]
+    max_file_bytes: Option<usize>,
+
+    /// Render image attachments (png, jpg, gif, webp, bmp, svg) inline as `![name](url)`
+    /// instead of as links
+    #[arg(long = "embed-images")]
+    embed_images: bool,
+
+    /// Link each message header to the original message (`[↗](url)`), built from the
+    /// `channels.url` and `messages.message_id` columns when the archive has both
+    #[arg(long = "jump-links")]
+    jump_links: bool,
+
+    /// Render the first line of each multi-line message in bold as a title above the rest
+    #[arg(long = "first-line-as-title")]
+    first_line_as_title: bool,
+
+    /// Start each day within a file with a `## YYYY-MM-DD` subheading
+    #[arg(long = "day-dividers")]
+    day_dividers: bool,
+
+    /// Write an Obsidian vault: `<channel>/<period>.md` files tagged `channel/<name>` in YAML
+    /// frontmatter, wikilinks between files and to replied-to messages, and channel mentions as
+    /// tags (implies --anchors, --nav-footer and --resolve-mentions)
+    #[arg(long = "obsidian", conflicts_with = "by_weekday")]
+    obsidian: bool,
+
+    /// Place the contents of this file (e.g. a license notice) at the top of every Markdown and
+    /// HTML file, above the heading
+    #[arg(long = "header-file", value_name = "PATH")]
+    header_file: Option<PathBuf>,
+
+    /// Prefix each message with an HTML anchor `<a id="msg-<id>"></a>` and link reply quotes
+    /// to the quoted message's anchor when it is in the same file
+    #[arg(long = "anchors")]
+    anchors: bool,
+
+    /// Period covered by each channel file: day, month or year. Several values (e.g. `year,month`)
+    /// write one set of files per granularity into subdirectories named after them
+    #[arg(long = "granularity", value_name = "LIST", value_delimiter = ',', default_value = "month")]
+    granularity: Vec<Granularity>,
+
+    /// Also write `index.md` linking every channel-month file
+    #[arg(long = "index")]
+    index: bool,
+
+    /// Order of channels in the index: alpha, activity (most messages first) or id
+    #[arg(long = "channel-sort", value_name = "ORDER", default_value = "alpha")]
+    channel_sort: ChannelSort,
+
+    /// Group channels in the index under `## Category` sections (needs a `channels.category` column)
+    #[arg(long = "categories")]
+    categories: bool,
+
+    /// Append links to the previous and next month of the same channel to each file
+    #[arg(long = "nav-footer")]
+    nav_footer: bool,
+
+    /// Show timestamps and split files into days, months and weekdays in this IANA timezone
+    /// (e.g. `America/New_York` or `UTC`) instead of the host's, for the same output on every machine
+    #[arg(long = "timezone", value_name = "TZ")]
+    timezone: Option<OutputTimezone>,
+
+    /// Show timestamps exactly as stored in the database instead of converted to the output timezone
+    #[arg(long = "raw-timestamps")]
+    raw_timestamps: bool,
+
+    /// Name the source database file in each message header (useful with several --input-db)
+    #[arg(long = "show-source")]
+    show_source: bool,
+
+    /// Soft-wrap message content at this many columns without breaking words (code blocks are exempt)
+    #[arg(long = "wrap", value_name = "COLUMNS")]
+    wrap: Option<usize>,
+
+    /// Render consecutive messages from the same author under a single header
+    #[arg(long = "group-consecutive")]
+    group_consecutive: bool,
+
+    /// Only group consecutive messages posted within this many minutes of each other
+    #[arg(long = "group-window", value_name = "MINUTES", requires = "group_consecutive")]
+    group_window: Option<u64>,
+
+    /// Drop messages flagged as deleted instead of rendering them as `*[deleted]*`
+    #[arg(long = "exclude-deleted")]
+    exclude_deleted: bool,
+
+    /// Remove control characters (other than newlines and tabs) from message content
+    #[arg(long = "sanitize-content")]
+    sanitize_content: bool,
+
+    /// Only export messages posted in threads (channels with a parent channel)
+    #[arg(long = "only-threads", conflicts_with = "no_threads")]
+    only_threads: bool,
+
+    /// Only export messages posted outside threads
+    #[arg(long = "no-threads")]
+    no_threads: bool,
+
+    /// Drop forwarded and crossposted messages (shown with a `↪ forwarded` label otherwise)
+    #[arg(long = "no-forwards")]
+    no_forwards: bool,
+
+    /// Only export channels matching this name, ignoring case; glob patterns such as `team-*` or
+    /// `gen-?` are supported. Can be repeated to keep several channels
+    #[arg(long = "channel", visible_alias = "include-channel", value_name = "PATTERN")]
+    channels: Vec<String>,
+
+    /// Drop channels matching this name or glob pattern, ignoring case, after --channel has been
+    /// applied. Can be repeated
+    #[arg(long = "exclude-channel", value_name = "PATTERN")]
+    exclude_channels: Vec<String>,
+
+    /// Only export messages whose reactions add up to at least N (requires a `messages.reactions` column)
+    #[arg(long = "min-reactions", value_name = "N")]
+    min_reactions: Option<u64>,
+
+    /// Only export pinned messages (requires a `messages.pinned` column)
+    #[arg(long = "pinned-only")]
+    pinned_only: bool,
+
+    /// Leave out every message by this user, given as username or user id (repeatable), from all
+    /// outputs and statistics
+    #[arg(long = "block-user", value_name = "NAME_OR_ID")]
+    block_users: Vec<String>,
+
+    /// Like --block-user for each line of this file (blank lines and `#` comments are skipped)
+    #[arg(long = "block-users-file", value_name = "PATH")]
+    block_users_file: Option<PathBuf>,
+
+    /// Only export authors with at least N messages, counted after the other filters
+    #[arg(long = "min-author-messages", value_name = "N")]
+    min_author_messages: Option<usize>,
+
+    /// Only export authors with at most N messages, counted after the other filters
+    #[arg(long = "max-author-messages", value_name = "N")]
+    max_author_messages: Option<usize>,
+
+    /// Apply Unicode NFC normalization to channel names and message content, so visually
+    /// identical names share one file and identical content is detected as a duplicate
+    #[arg(long = "normalize-unicode")]
+    normalize_unicode: bool,
+
+    /// Label messages whose content (trimmed, lowercased, whitespace collapsed) repeats another
+    /// message's with `⚠ duplicate`, and count the duplicate clusters in the summary JSON
+    /// (within each channel only with --per-channel-fetch)
+    #[arg(long = "flag-duplicates")]
+    flag_duplicates: bool,
+
+    /// Drop messages that exactly repeat an earlier one (same channel, author, timestamp and
+    /// content), e.g. from overlapping --input-db sources, and print how many were removed
+    #[arg(long = "dedupe")]
+    dedupe: bool,
+
+    /// Detect each message's language and show its ISO 639-3 code (e.g. `eng`) in the header;
+    /// short or ambiguous messages get none
+    #[arg(long = "detect-language")]
+    detect_language: bool,
+
+    /// Only export messages detected as this language, by ISO 639-3 code such as `spa`
+    /// (implies --detect-language)
+    #[arg(long = "language", value_name = "CODE")]
+    language: Option<String>,
+
+    /// Only export messages newer than the timestamp stored in this state file by the previous
+    /// run, then store the newest exported message's timestamp (a missing file exports everything)
+    #[arg(long = "since-last-run", value_name = "STATE_FILE")]
+    since_last_run: Option<PathBuf>,
+
+    /// Only export messages from this calendar day (YYYY-MM-DD) in the output timezone
+    /// (`--timezone`, default local)
+    #[arg(long = "date", value_name = "YYYY-MM-DD", value_parser = parse_day)]
+    date: Option<String>,
+
+    /// Only export messages from this calendar day (YYYY-MM-DD) or later, in the output timezone
+    /// (`--timezone`, default local).
+    /// With --from or --to, messages whose timestamp cannot be parsed are dropped
+    #[arg(long = "from", value_name = "YYYY-MM-DD", value_parser = parse_day)]
+    from: Option<String>,
+
+    /// Only export messages up to and including this calendar day (YYYY-MM-DD), in the output
+    /// timezone (`--timezone`, default local)
+    #[arg(long = "to", value_name = "YYYY-MM-DD", value_parser = parse_day)]
+    to: Option<String>,
+
+    /// Only export messages containing at least one http(s) link
+    #[arg(long = "links-only")]
+    links_only: bool,
+
+    /// Only export messages with at least one attachment
+    #[arg(long = "attachments-only")]
+    attachments_only: bool,
+
+    /// Number messages within their channel across the whole export, shown as `#N` in headers
+    #[arg(long = "number-messages")]
+    number_messages: bool,
+
+    /// Fetch and write one channel at a time to bound memory use (outputs that need the whole
+    /// export at once are unavailable). --flag-duplicates then only compares messages within a
+    /// channel; --dedupe is unaffected, as exact duplicates always share a channel
+    #[arg(
+        long = "per-channel-fetch",
+        conflicts_with_all = ["index", "by_weekday", "single_file", "roster", "links_txt", "reactions_csv", "records_json", "summary_json", "stats", "min_author_messages", "max_author_messages"]
+    )]
+    per_channel_fetch: bool,
+
+    /// Prepared statements kept open for reuse by --per-channel-fetch on SQLite input (0 re-prepares
+    /// the query for every channel)
+    #[arg(long = "statement-cache-size", value_name = "N", default_value_t = 16, requires = "per_channel_fetch")]
+    statement_cache_size: usize,
+
+    /// Handling of messages without a usable timestamp: bucket (file under `unknown`), drop,
+    /// snowflake (derive from the message id) or inherit (reuse the previous message's)
+    #[arg(long = "null-timestamp-policy", value_name = "POLICY", default_value = "bucket")]
+    null_timestamp_policy: NullTimestampPolicy,
+
+    /// Fail instead of exporting when any non-empty timestamp cannot be parsed, listing a few
+    /// of the offending values; empty timestamps are still handled by --null-timestamp-policy
+    #[arg(long = "strict-timestamps")]
+    strict_timestamps: bool,
+
+    /// JSON object mapping user ids to a canonical identity name, e.g. `{"111": "alice", "222": "alice"}`;
+    /// messages from all ids of an identity are attributed to one author
+    #[arg(long = "identity-map", value_name = "PATH")]
+    identity_map: Option<String>,
+
+    /// Replace user mentions with `@username` using the users table, and channel mentions
+    /// with `#name` using the channels table
+    #[arg(long = "resolve-mentions")]
+    resolve_mentions: bool,
+
+    /// Write the user ids of mentions that could not be resolved to this path, one per line
+    /// (implies --resolve-mentions)
+    #[arg(long = "unresolved-mentions", value_name = "PATH")]
+    unresolved_mentions: Option<String>,
+
+    /// Replace user, channel and role mentions in message content with `[mention]`
+    #[arg(long = "strip-mentions")]
+    strip_mentions: bool,
+
+    /// Replace matches of this regular expression in message content with `[REDACTED]` (repeatable)
+    #[arg(long = "redact", value_name = "REGEX")]
+    redact: Vec<String>,
+
+    /// Redact email addresses
+    #[arg(long = "redact-emails")]
+    redact_emails: bool,
+
+    /// Redact phone numbers
+    #[arg(long = "redact-phones")]
+    redact_phones: bool,
+
+    /// Redact Discord tokens
+    #[arg(long = "redact-tokens")]
+    redact_tokens: bool,
+
+    /// Also write a sorted list of unique authors to this path (`.csv` for CSV, Markdown otherwise)
+    #[arg(long = "roster")]
+    roster: Option<String>,
+
+    /// Also write one HTML page per channel (`<channel>.html`) into the output folder
+    #[arg(long = "html")]
+    html: bool,
+
+    /// Write the --html pages into this folder instead of the output folder (implies --html)
+    #[arg(long = "out-html", value_name = "DIR")]
+    out_html: Option<String>,
+
+    /// HTML template for --html pages, with `{{channel}}`, `{{messages}}` and optional `{{style}}` placeholders
+    #[arg(long = "html-template", value_name = "PATH", requires = "html_output")]
+    html_template: Option<String>,
+
+    /// Also write every link found in message content to this path, one per line
+    #[arg(long = "links-txt", value_name = "PATH")]
+    links_txt: Option<String>,
+
+    /// Also write every reaction to this path as CSV: one `message_id,emoji,count` row per emoji
+    #[arg(long = "reactions-csv", value_name = "PATH")]
+    reactions_csv: Option<String>,
+
+    /// Also write every exported record to this path as a JSON array
+    #[arg(long = "records-json", visible_alias = "out-json", value_name = "PATH")]
+    records_json: Option<String>,
+
+    /// In JSON records output, omit fields that are null or empty lists
+    #[arg(long = "compact-json")]
+    compact_json: bool,
+
+    /// In machine-readable outputs, write timestamps as integer Unix seconds (null when unparseable)
+    #[arg(long = "epoch-timestamps")]
+    epoch_timestamps: bool,
+
+    /// Strip ``` fences from message content in plain (non-Markdown) outputs, keeping the code
+    #[arg(long = "flatten-code-blocks-to-text")]
+    flatten_code_blocks: bool,
+
+    /// With --flatten-code-blocks-to-text, keep each block's language tag as a `[lang]` line
+    #[arg(long = "keep-code-language", requires = "flatten_code_blocks")]
+    keep_code_language: bool,
+
+    /// Also write aggregate message counts (per channel, author, month), the date range and emoji/link stats as JSON
+    #[arg(long = "summary-json")]
+    summary_json: Option<String>,
+
+    /// Print a table of each channel's first and last message timestamps and active span
+    /// in days to stderr after exporting
+    #[arg(long = "stats")]
+    stats: bool,
+
+    /// Fixed generation time (ISO 8601) stamped into outputs as `generated_at` and used by
+    /// --relative-time, for reproducible builds
+    /// Defaults to SOURCE_DATE_EPOCH when set, otherwise the current time
+    #[arg(long = "now", value_name = "TIMESTAMP")]
+    now: Option<String>,
+
+    /// Follow each message timestamp with how long ago it was, e.g. `(3 months ago)`, relative to --now
+    #[arg(long = "relative-time")]
+    relative_time: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check that a database has the expected schema and parseable timestamps, without exporting
+    Validate {
+        /// Path to the SQLite database file (or `file:` URI) to check
+        #[arg(short = 'i', long = "input-db")]
+        input_db: String,
+        /// Also write the report as JSON, listing expected vs found tables and columns
+        #[arg(long = "diagnostics-json", value_name = "PATH")]
+        diagnostics_json: Option<PathBuf>,
+        /// JSON file renaming the tables and columns, as for exports
+        #[arg(long = "schema", value_name = "PATH")]
+        schema: Option<PathBuf>,
+    },
+    /// Browse channels and messages in an interactive terminal UI instead of exporting
+    Browse {
+        /// Path to the SQLite database file (or `file:` URI) to browse
+        #[arg(short = 'i', long = "input-db")]
+        input_db: String,
+        /// JSON file renaming the tables and columns, as for exports
+        #[arg(long = "schema", value_name = "PATH")]
+        schema: Option<PathBuf>,
+    },
+}
+
+/// Reads the `--schema` file, or the default schema when none is given
+fn load_schema(path: Option<&Path>) -> core::ports::Result<SchemaConfig> {
+    match path {
+        Some(path) => SchemaConfig::from_json(&fs::read_to_string(path)?),
+        None => Ok(SchemaConfig::default()),
+    }
+}
+
+/// Prints a validation report and returns the process exit code (non-zero on problems)
+fn run_validate(input_db: &str, diagnostics_json: Option<&Path>, schema: Option<&Path>) -> i32 {
+    let repository = load_schema(schema)
+        .and_then(|schema| SqliteDataRepository::new_with_schema(input_db.to_string(), schema));
+    let report = match repository.and_then(|repository| repository.validate()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error validating database: {}", e);
+            return 1;
+        }
+    };
+
+    if let Some(path) = diagnostics_json {
+        if let Err(e) = report.to_json().and_then(|json| Ok(fs::write(path, json)?)) {
+            eprintln!("Error writing diagnostics to {}: {}", path.display(), e);
+            return 1;
+        }
+    }
+
+    for item in &report.missing {
+        eprintln!("Missing required table or column: {}", item);
+    }
+    println!("Messages: {}", report.message_count);
+    println!("Unparseable timestamps: {}", report.unparseable_timestamps);
+    if !report.channel_ranges.is_empty() {
+        print!("\n{}\n", format_channel_ranges(&report.channel_ranges));
+    }
+
+    if report.is_ok() {
+        println!("{} looks extractable", input_db);
+        0
+    } else {
+        eprintln!("{} has problems", input_db);
+        1
+    }
+}
+
+/// Opens the destination of a tar archive, where `-` means stdout
+fn open_tar_output(target: &str) -> io::Result<TarOutput> {
+    if target == "-" {
+        Ok(Box::new(io::stdout()))
+    } else {
+        Ok(Box::new(File::create(target)?))
+    }
+}
+
+/// Prints the size of each file a dry run would have written, and the total
+fn print_size_estimate(sink: &MeasuringSink) {
+    let sizes = sink.sizes();
+    for (path, size) in &sizes {
+        println!("{:>10}  {}", format_bytes(*size), path.display());
+    }
+    println!("{:>10}  total ({} files, nothing written)", format_bytes(sink.total()), sizes.len());
 }
 
 fn main() {
     let cli = Cli::parse();
+    let timezone = cli.timezone.unwrap_or_default();
+    match &cli.command {
+        Some(Command::Validate {
+            input_db,
+            diagnostics_json,
+            schema,
+        }) => std::process::exit(run_validate(input_db, diagnostics_json.as_deref(), schema.as_deref())),
+        Some(Command::Browse { input_db, schema }) => {
+            let repository = load_schema(schema.as_deref())
+                .and_then(|schema| SqliteDataRepository::new_with_schema(input_db.clone(), schema))
+                .map(|repository| repository.with_timezone(timezone));
+            if let Err(e) = repository.and_then(|repository| tui_adapter::browse(&repository, timezone)) {
+                eprintln!("Error browsing database: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+    let log_level = if cli.verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    if let Err(e) = StderrLogger::init(cli.log_format, log_level) {
+        eprintln!("Error initializing logging: {}", e);
+    }
 
-    // Instantiate concrete implementations of secondary adapters
-    let data_repository: Box<dyn DataRepository> = Box::new(
-        SqliteDataRepository::new(cli.input_db.clone())
-    );
-    
-    let markdown_writer: Box<dyn MarkdownWriter> = Box::new(
-        MarkdownWriterAdapter::new(cli.output_folder.clone())
-    );
+    // A dry run measures every file in memory instead of writing anything
+    let measuring_sink = cli.dry_run.then(|| Arc::new(MeasuringSink::default()));
+    // With --tar every writer emits into one archive instead of the filesystem
+    let tar_target = cli.tar.as_deref().filter(|_| !cli.dry_run);
+    let tar_sink: Option<Arc<TarSink<TarOutput>>> = match tar_target.map(open_tar_output) {
+        Some(Ok(output)) => Some(Arc::new(TarSink::new(output))),
+        Some(Err(e)) => {
+            eprintln!("Error opening tar output: {}", e);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    let sink_for = |root: &str| -> Arc<dyn OutputSink> {
+        match (&measuring_sink, &tar_sink) {
+            (Some(sink), _) => Arc::new(PrefixedSink::new(sink.clone(), root)),
+            (None, Some(sink)) => sink.clone(),
+            (None, None) => Arc::new(DirectorySink::new(root).with_resume(cli.resume).with_fsync(cli.fsync)),
+        }
+    };
+    // `-` sends an output to stdout, which only one output can own
+    let stdout_outputs = [
+        cli.output_folder.as_deref().filter(|_| tar_sink.is_none()),
+        cli.single_file.as_deref().filter(|_| tar_sink.is_none()),
+        cli.tar.as_deref(),
+        cli.roster.as_deref(),
+        cli.reactions_csv.as_deref(),
+        cli.links_txt.as_deref(),
+        cli.records_json.as_deref(),
+        cli.summary_json.as_deref(),
+        cli.unresolved_mentions.as_deref(),
+    ]
+    .iter()
+    .filter(|target| **target == Some("-"))
+    .count();
+    if stdout_outputs > 1 {
+        eprintln!("Only one output can be written to stdout (`-`)");
+        std::process::exit(1);
+    }
+    let file_sink = |path: &str| -> Arc<dyn OutputSink> {
+        if path == "-" && !cli.dry_run {
+            Arc::new(StreamSink::new(io::stdout()))
+        } else {
+            sink_for("")
+        }
+    };
+    let output_folder = cli.output_folder.clone().unwrap_or_default();
+    let markdown_to_stdout = tar_sink.is_none() && !cli.dry_run && output_folder == "-";
+    // Reject paths of the wrong kind before any work; archive and dry-run paths are never opened
+    if std::env::args().any(|arg| arg == "--output-file" || arg.starts_with("--output-file=")) {
+        log::warn!("--output-file is deprecated and names a directory; use --output-dir");
+    }
+    if tar_sink.is_none() && !cli.dry_run {
+        let single_files = [
+            &cli.single_file,
+            &cli.roster,
+            &cli.reactions_csv,
+            &cli.links_txt,
+            &cli.records_json,
+            &cli.summary_json,
+            &cli.unresolved_mentions,
+        ];
+        for path in single_files.into_iter().flatten().filter(|path| *path != "-") {
+            if let Err(e) = check_output_file(Path::new(path)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let existing_output = if cli.clean {
+        ExistingOutput::Clean
+    } else if cli.overwrite || cli.resume || cli.append {
+        ExistingOutput::Overwrite
+    } else {
+        ExistingOutput::Fail
+    };
+    let writes_output_folder = tar_sink.is_none() && !markdown_to_stdout && cli.single_file.is_none() && !cli.dry_run;
+    if writes_output_folder {
+        if let Err(e) = prepare_output_folder(Path::new(&output_folder), existing_output) {
+            eprintln!("Error preparing output folder: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let out_html = cli.out_html.as_deref().filter(|_| tar_sink.is_none() && !cli.dry_run);
+    // HTML pages are cleaned along with the Markdown files, wherever they are written
+    let html_folder = out_html.or(Some(output_folder.as_str()).filter(|_| cli.html && writes_output_folder));
+    if let Some(html_folder) = html_folder.filter(|_| cli.clean) {
+        match clean_html_pages(Path::new(html_folder)) {
+            Ok(removed) => log::info!(count = removed; "removed previous html pages"),
+            Err(e) => {
+                eprintln!("Error cleaning html pages: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(html_folder) = out_html {
+        if let Err(e) = prepare_output_folder(Path::new(html_folder), existing_output) {
+            eprintln!("Error preparing --out-html folder: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Register the built-in adapters, selected by --input-format and --output-format
+    let mut registry = Registry::default();
+    let top_channels = cli.top_channels;
+    let statement_cache_size = cli.statement_cache_size;
+    let schema = match load_schema(cli.schema.as_deref()) {
+        Ok(schema) => schema,
+        Err(e) => {
+            eprintln!("Invalid --schema: {}", e);
+            std::process::exit(1);
+        }
+    };
+    registry.register_repository("sqlite", move |source| {
+        let mut sqlite_repository = SqliteDataRepository::new_with_schema(source.to_string(), schema.clone())?
+            .with_statement_cache_capacity(statement_cache_size)
+            .with_timezone(timezone);
+        if let Some(limit) = top_channels {
+            sqlite_repository = sqlite_repository.with_top_channels(limit);
+        }
+        Ok(Box::new(sqlite_repository))
+    });
+    registry.register_repository("json", move |source| {
+        Ok(Box::new(JsonDataRepository::new(source.to_string()).with_timezone(timezone)))
+    });
+
+    // clap requires at least one --input-db whenever no subcommand is given
+    let repositories: core::ports::Result<Vec<Box<dyn DataRepository>>> = cli
+        .input_db
+        .iter()
+        .map(|input_db| registry.repository(&cli.input_format, input_db))
+        .collect();
+    let mut repositories = match repositories {
+        Ok(repositories) => repositories,
+        Err(e) => {
+            eprintln!("Error opening input: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let data_repository: Box<dyn DataRepository> = if repositories.len() == 1 {
+        repositories.remove(0)
+    } else {
+        Box::new(MergingRepository::new(repositories))
+    };
+
+    let source_date_epoch = std::env::var("SOURCE_DATE_EPOCH").ok();
+    let generated_at = match resolve_generated_at(cli.now.as_deref(), source_date_epoch.as_deref()) {
+        Ok(generated_at) => generated_at,
+        Err(e) => {
+            eprintln!("Error resolving generation time: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let header = match cli.header_file.as_deref().map(fs::read_to_string).transpose() {
+        Ok(header) => header,
+        Err(e) => {
+            eprintln!("Error reading --header-file: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let markdown_options = MarkdownOptions {
+        reply_depth: cli.reply_depth,
+        grouping: if cli.by_weekday {
+            Grouping::Weekday
+        } else if cli.one_file_per_message {
+            Grouping::Message
+        } else {
+            Grouping::ChannelMonth
+        },
+        wrap: cli.wrap,
+        group_consecutive: cli.group_consecutive,
+        group_window: cli.group_window,
+        nav_footer: cli.nav_footer || cli.obsidian,
+        max_files: cli.max_files,
+        max_file_bytes: cli.max_file_bytes,
+        embed_images: cli.embed_images,
+        jump_links: cli.jump_links,
+        first_line_as_title: cli.first_line_as_title,
+        header: header.clone(),
+        relative_to: cli.relative_time.then(|| generated_at.clone()),
+        anchors: cli.anchors || cli.obsidian,
+        obsidian: cli.obsidian,
+        day_dividers: cli.day_dividers,
+        single_file: cli.single_file.clone(),
+        append: cli.append,
+        index: cli.index,
+        channel_sort: cli.channel_sort,
+        categories: cli.categories,
+        raw_timestamps: cli.raw_timestamps,
+        show_source: cli.show_source,
+        // Set per writer below, one writer per requested granularity
+        granularity: Granularity::default(),
+        timezone,
+    };
+    let markdown_sink: Arc<dyn OutputSink> = if let Some(path) = &cli.single_file {
+        file_sink(path)
+    } else if markdown_to_stdout {
+        // Only the multi-file Markdown layout needs a separator line before each file
+        Arc::new(StreamSink::new(io::stdout()).with_separators(cli.output_format == "markdown"))
+    } else {
+        sink_for(&output_folder)
+    };
+    // With several granularities each set of files goes into a subdirectory named after it
+    let mut granularities = cli.granularity.clone();
+    granularities.sort();
+    granularities.dedup();
+    if cli.single_file.is_some() && granularities.len() > 1 {
+        eprintln!("--single-file writes one file and takes a single --granularity");
+        std::process::exit(1);
+    }
+    // Other writers produce one file for the whole export, which each channel would replace
+    if cli.per_channel_fetch && cli.output_format != "markdown" {
+        eprintln!("--per-channel-fetch only supports --output-format markdown");
+        std::process::exit(1);
+    }
+    registry.register_writer("markdown", move |sink| {
+        let writers = granularities
+            .iter()
+            .map(|&granularity| -> Box<dyn MarkdownWriter> {
+                let sink: Arc<dyn OutputSink> = if granularities.len() > 1 {
+                    Arc::new(PrefixedSink::new(sink.clone(), granularity.name()))
+                } else {
+                    sink.clone()
+                };
+                let options = MarkdownOptions {
+                    granularity,
+                    ..markdown_options.clone()
+                };
+                Box::new(MarkdownWriterAdapter::with_sink(sink, options))
+            })
+            .collect();
+        Ok(Box::new(WriterSet(writers)))
+    });
+    // With --single-file the one file these writers produce goes to its path
+    let epoch_timestamps = cli.epoch_timestamps;
+    let single_file = cli.single_file.clone();
+    registry.register_writer("json", move |sink| {
+        let writer = ChannelsJsonWriter::with_sink(sink).with_epoch_timestamps(epoch_timestamps);
+        Ok(Box::new(match single_file.clone() {
+            Some(path) => writer.with_output_path(path),
+            None => writer,
+        }))
+    });
+    let single_file = cli.single_file.clone();
+    registry.register_writer("csv", move |sink| {
+        let writer = CsvWriter::with_sink(sink).with_epoch_timestamps(epoch_timestamps);
+        Ok(Box::new(match single_file.clone() {
+            Some(path) => writer.with_output_path(path),
+            None => writer,
+        }))
+    });
+    let markdown_writer = match registry.writer(&cli.output_format, markdown_sink.clone()) {
+        Ok(writer) => writer,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let channel_filter = |patterns: &[String]| match (!patterns.is_empty()).then(|| ChannelFilter::new(patterns)) {
+        Some(Ok(filter)) => Some(filter),
+        Some(Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    let channels = channel_filter(&cli.channels);
+    let exclude_channels = channel_filter(&cli.exclude_channels);
 
     // Instantiate the core business service with dependency injection
-    let service = core::application::ExtractionServiceImpl::new(
+    let mut blocked_users: HashSet<String> = cli.block_users.iter().cloned().collect();
+    if let Some(path) = &cli.block_users_file {
+        match fs::read_to_string(path) {
+            Ok(list) => blocked_users.extend(
+                list.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            ),
+            Err(e) => {
+                eprintln!("Error reading --block-users-file: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let last_run = cli.since_last_run.as_ref().map(LastRun::new);
+    let since = match last_run.as_ref().map(LastRun::load).transpose() {
+        Ok(since) => since.flatten(),
+        Err(e) => {
+            eprintln!("Error reading --since-last-run state: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let extraction_options = ExtractionOptions {
+        exclude_deleted: cli.exclude_deleted,
+        null_timestamp_policy: cli.null_timestamp_policy,
+        attachments_only: cli.attachments_only,
+        exclude_forwards: cli.no_forwards,
+        links_only: cli.links_only,
+        channels,
+        exclude_channels,
+        timezone,
+        date: cli.date.clone(),
+        from: cli.from.clone(),
+        to: cli.to.clone(),
+        since,
+        number_messages: cli.number_messages,
+        flag_duplicates: cli.flag_duplicates,
+        dedupe: cli.dedupe,
+        detect_language: cli.detect_language,
+        language: cli.language.clone(),
+        strict_timestamps: cli.strict_timestamps,
+        normalize_unicode: cli.normalize_unicode,
+        min_reactions: cli.min_reactions,
+        blocked_users,
+        min_author_messages: cli.min_author_messages,
+        max_author_messages: cli.max_author_messages,
+        pinned_only: cli.pinned_only,
+        per_channel_fetch: cli.per_channel_fetch,
+        threads: if cli.only_threads {
+            ThreadFilter::OnlyThreads
+        } else if cli.no_threads {
+            ThreadFilter::NoThreads
+        } else {
+            ThreadFilter::All
+        },
+    };
+    let resolve_mentions = cli.resolve_mentions || cli.obsidian;
+    let usernames = if resolve_mentions || cli.unresolved_mentions.is_some() {
+        match data_repository.fetch_usernames() {
+            Ok(usernames) => Some(usernames),
+            Err(e) => {
+                eprintln!("Error reading users: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let channel_names = if resolve_mentions {
+        match data_repository.fetch_channel_names() {
+            Ok(channel_names) => Some(channel_names),
+            Err(e) => {
+                eprintln!("Error reading channels: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let mut service = ExtractionServiceImpl::new(
         data_repository,
         markdown_writer,
-    );
+    )
+    .with_options(extraction_options);
+    if cli.sanitize_content {
+        service = service.with_transform(Box::new(SanitizeContent));
+    }
+    if let Some(identity_map) = cli.identity_map.as_deref() {
+        let merge = std::fs::read_to_string(identity_map)
+            .map_err(|e| e.into())
+            .and_then(|json| MergeIdentities::from_json(&json));
+        match merge {
+            Ok(merge) => service = service.with_transform(Box::new(merge)),
+            Err(e) => {
+                eprintln!("Invalid --identity-map: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // Resolution runs first so stripping only removes what could not be resolved
+    if let Some(usernames) = usernames {
+        service = service.with_transform(Box::new(ResolveMentions::new(usernames)));
+    }
+    if let Some(channel_names) = channel_names {
+        let mut resolve = ResolveChannelMentions::new(channel_names);
+        if cli.obsidian {
+            resolve = resolve.with_format(|name| format!("#{}", channel_tag(name)));
+        }
+        service = service.with_transform(Box::new(resolve));
+    }
+    if cli.strip_mentions {
+        service = service.with_transform(Box::new(StripMentions::new("[mention]".to_string())));
+    }
+    // Redaction runs last so it also covers text introduced by earlier transforms
+    let mut redact_patterns = cli.redact.clone();
+    for (enabled, preset) in [
+        (cli.redact_emails, EMAIL_PATTERN),
+        (cli.redact_phones, PHONE_PATTERN),
+        (cli.redact_tokens, TOKEN_PATTERN),
+    ] {
+        if enabled {
+            redact_patterns.push(preset.to_string());
+        }
+    }
+    if !redact_patterns.is_empty() {
+        match Redact::new(&redact_patterns, "[REDACTED]".to_string()) {
+            Ok(redact) => service = service.with_transform(Box::new(redact)),
+            Err(e) => {
+                eprintln!("Invalid --redact pattern: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if cli.html || cli.out_html.is_some() {
+        let template = match cli.html_template.as_deref().map(std::fs::read_to_string) {
+            None => Ok(HtmlTemplate::default()),
+            Some(Ok(source)) => HtmlTemplate::parse(source),
+            Some(Err(e)) => Err(e.into()),
+        };
+        match template {
+            Ok(template) => {
+                let html_sink: Arc<dyn OutputSink> = match (&cli.out_html, &tar_sink) {
+                    // The archive holds the pages under their folder, next to the Markdown files
+                    (Some(html_folder), Some(tar_sink)) => Arc::new(PrefixedSink::new(tar_sink.clone(), html_folder)),
+                    (Some(html_folder), None) => sink_for(html_folder),
+                    (None, _) => markdown_sink.clone(),
+                };
+                let writer = HtmlWriter::with_sink(html_sink, template).with_header(header.clone());
+                service = service.with_writer(Box::new(writer));
+            }
+            Err(e) => {
+                eprintln!("Invalid --html-template: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(links_path) = cli.links_txt.clone() {
+        service = service.with_writer(Box::new(LinksWriter::with_sink(file_sink(&links_path), links_path)));
+    }
+    if let Some(reactions_path) = cli.reactions_csv.clone() {
+        let writer = ReactionsCsvWriter::with_sink(file_sink(&reactions_path), reactions_path);
+        service = service.with_writer(Box::new(writer));
+    }
+    if let Some(roster_path) = cli.roster.clone() {
+        service = service.with_writer(Box::new(RosterWriter::with_sink(file_sink(&roster_path), roster_path)));
+    }
+    if let Some(records_path) = cli.records_json.clone() {
+        let writer = JsonRecordsWriter::with_sink(file_sink(&records_path), records_path)
+            .with_epoch_timestamps(cli.epoch_timestamps)
+            .with_compact(cli.compact_json);
+        if cli.flatten_code_blocks {
+            let flatten = FlattenCodeBlocks::new(cli.keep_code_language);
+            service = service.with_writer(Box::new(TransformedWriter::new(Box::new(writer), vec![Box::new(flatten)])));
+        } else {
+            service = service.with_writer(Box::new(writer));
+        }
+    }
+    if cli.stats {
+        service = service.with_writer(Box::new(StatsWriter));
+    }
+    if let Some(summary_path) = cli.summary_json.clone() {
+        let writer = SummaryJsonWriter::with_sink(file_sink(&summary_path), summary_path);
+        service = service.with_writer(Box::new(writer.with_generated_at(Some(generated_at)).with_timezone(timezone)));
+    }
 
-    // Execute the primary port method
-    match service.execute_extraction() {
-        Ok(_) => {
-            println!("Successfully extracted messages to {}", cli.output_folder);
+    // Execute the primary port method, then close the archive if one is being written
+    // The last-run state is only advanced once everything else has succeeded
+    let result = service
+        .execute_extraction()
+        .and_then(|report| {
+            if let Some(path) = cli.unresolved_mentions.as_deref() {
+                let ids: String = report.unresolved_mentions.iter().map(|id| format!("{}\n", id)).collect();
+                file_sink(path).write_file(Path::new(path), ids.as_bytes())?;
+            }
+            Ok(report)
+        })
+        .and_then(|report| {
+            if let Some(sink) = &tar_sink {
+                sink.finish()?;
+            }
+            Ok(report)
+        })
+        .and_then(|report| {
+            match (&last_run, &report.latest_timestamp) {
+                (Some(last_run), Some(latest)) if !cli.dry_run => last_run.save(latest)?,
+                _ => {}
+            }
+            Ok(report)
+        });
+    match result {
+        Ok(report) => {
+            if cli.dedupe {
+                let removed = format!("Removed {} duplicate records", report.duplicates_removed);
+                // stdout may carry the output, as for the success message below
+                if stdout_outputs > 0 {
+                    eprintln!("{}", removed);
+                } else {
+                    println!("{}", removed);
+                }
+            }
+            match (&measuring_sink, cli.tar.as_deref()) {
+                (Some(sink), _) => print_size_estimate(sink),
+                // stdout carries the output, so report on stderr instead
+                _ if stdout_outputs > 0 => eprintln!("Successfully extracted messages to stdout"),
+                (None, Some(path)) => println!("Successfully extracted messages to {}", path),
+                (None, None) => match &cli.single_file {
+                    Some(path) => println!("Successfully extracted messages to {}", path),
+                    None => println!("Successfully extracted messages to {}", output_folder),
+                },
+            }
         }
         Err(e) => {
             eprintln!("Error during extraction: {}", e);
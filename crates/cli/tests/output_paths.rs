@@ -0,0 +1,196 @@
+//! Checks what the output flags create on disk, by running the built binary
+
+use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Creates a database with one message in #general and returns its path
+fn create_database(dir: &Path) -> String {
+    let path = dir.join("archive.db");
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            r#"
+            CREATE TABLE channels (id INTEGER PRIMARY KEY, name TEXT);
+            CREATE TABLE users (user_id TEXT PRIMARY KEY, username TEXT);
+            CREATE TABLE messages (channel_id INTEGER, user_id TEXT, timestamp TEXT, content TEXT);
+            INSERT INTO channels VALUES (1, 'general');
+            INSERT INTO users VALUES ('u1', 'alice');
+            INSERT INTO messages VALUES (1, 'u1', '2025-12-16T10:30:00Z', 'hello');
+            "#,
+        )
+        .unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sti-cli"))
+        .current_dir(dir)
+        .env("TZ", "UTC")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_output_dir_is_a_directory_of_channel_files() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    for flag in ["-o", "--output-folder", "--output-dir"] {
+        let output = run(dir.path(), &["-i", &database, flag, flag.trim_start_matches('-')]);
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let folder = dir.path().join(flag.trim_start_matches('-'));
+        assert!(folder.is_dir());
+        assert!(fs::read_to_string(folder.join("general-2025-12.md")).unwrap().contains("hello"));
+    }
+}
+
+#[test]
+fn test_deprecated_output_file_still_names_a_directory() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    let output = run(dir.path(), &["-i", &database, "--output-file", "report.md"]);
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--output-file is deprecated"));
+    assert!(dir.path().join("report.md").join("general-2025-12.md").is_file());
+}
+
+#[test]
+fn test_output_dir_rejects_an_existing_file() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+    fs::write(dir.path().join("report.md"), "keep me").unwrap();
+
+    let output = run(dir.path(), &["-i", &database, "-o", "report.md"]);
+
+    assert!(!output.status.success());
+    assert_eq!(fs::read_to_string(dir.path().join("report.md")).unwrap(), "keep me");
+}
+
+#[test]
+fn test_single_file_is_one_markdown_file() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    let output = run(dir.path(), &["-i", &database, "--single-file", "export.md"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let content = fs::read_to_string(dir.path().join("export.md")).unwrap();
+    assert!(content.starts_with("# #general\n\n## 2025-12\n\n"));
+    assert!(content.contains("hello"));
+}
+
+#[test]
+fn test_single_file_takes_the_output_of_other_formats() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    for (format, path) in [("csv", "export.csv"), ("json", "export.json")] {
+        let output = run(dir.path(), &["-i", &database, "--single-file", path, "--format", format]);
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(fs::read_to_string(dir.path().join(path)).unwrap().contains("hello"));
+    }
+    assert!(!dir.path().join("messages.csv").exists());
+    assert!(!dir.path().join("channels.json").exists());
+}
+
+#[test]
+fn test_json_to_stdout_is_plain_json() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    let output = run(dir.path(), &["-i", &database, "-o", "-", "--format", "json"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(written["general"][0]["content"], "hello");
+}
+
+#[test]
+fn test_per_channel_fetch_writes_the_same_channel_files() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+    Connection::open(&database)
+        .unwrap()
+        .execute_batch(
+            r#"
+            INSERT INTO channels VALUES (2, 'random');
+            INSERT INTO messages VALUES (2, 'u1', '2025-12-17T09:00:00Z', 'hey');
+            INSERT INTO messages VALUES (1, 'u1', '2026-01-02T08:00:00Z', 'happy new year');
+            "#,
+        )
+        .unwrap();
+
+    let all_at_once = run(dir.path(), &["-i", &database, "-o", "all", "--now", "2026-01-03T00:00:00Z"]);
+    let per_channel = run(dir.path(), &["-i", &database, "-o", "split", "--now", "2026-01-03T00:00:00Z", "--per-channel-fetch"]);
+
+    assert!(all_at_once.status.success(), "{}", String::from_utf8_lossy(&all_at_once.stderr));
+    assert!(per_channel.status.success(), "{}", String::from_utf8_lossy(&per_channel.stderr));
+    for file in ["general-2025-12.md", "general-2026-01.md", "random-2025-12.md"] {
+        assert_eq!(
+            fs::read_to_string(dir.path().join("split").join(file)).unwrap(),
+            fs::read_to_string(dir.path().join("all").join(file)).unwrap(),
+        );
+    }
+    assert_eq!(fs::read_dir(dir.path().join("split")).unwrap().count(), 3);
+}
+
+#[test]
+fn test_per_channel_fetch_rejects_single_file_outputs() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    for args in [["--single-file", "export.md", "--format", "markdown"], ["-o", "out", "--format", "json"]] {
+        let output = run(dir.path(), &[&["-i", database.as_str(), "--per-channel-fetch"][..], &args].concat());
+
+        assert!(!output.status.success());
+    }
+    assert!(!dir.path().join("export.md").exists());
+    assert!(!dir.path().join("out").join("channels.json").exists());
+}
+
+#[test]
+fn test_each_format_lands_at_its_configured_path() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+    Connection::open(&database)
+        .unwrap()
+        .execute_batch("INSERT INTO channels VALUES (2, 'random'); INSERT INTO messages VALUES (2, 'u1', '2025-12-17T09:00:00Z', 'hey');")
+        .unwrap();
+    let outputs = ["--out-markdown", "notes", "--out-html", "site", "--out-json", "data.json"];
+
+    let first = run(dir.path(), &[&["-i", database.as_str()][..], &outputs].concat());
+    let again = run(dir.path(), &[&["-i", database.as_str()][..], &outputs].concat());
+    let cleaned = run(dir.path(), &[&["-i", database.as_str(), "--clean", "--exclude-channel", "random"][..], &outputs].concat());
+
+    assert!(first.status.success(), "{}", String::from_utf8_lossy(&first.stderr));
+    assert!(!again.status.success());
+    assert!(cleaned.status.success(), "{}", String::from_utf8_lossy(&cleaned.stderr));
+    assert!(fs::read_to_string(dir.path().join("notes/general-2025-12.md")).unwrap().contains("hello"));
+    assert!(fs::read_to_string(dir.path().join("site/general.html")).unwrap().contains("hello"));
+    assert!(fs::read_to_string(dir.path().join("data.json")).unwrap().contains("hello"));
+    // The second, cleaned run leaves nothing of the excluded channel behind
+    assert!(!dir.path().join("notes/random-2025-12.md").exists());
+    assert!(!dir.path().join("site/random.html").exists());
+}
+
+#[test]
+fn test_out_html_keeps_its_folder_inside_a_tar_archive() {
+    let dir = TempDir::new().unwrap();
+    let database = create_database(dir.path());
+
+    let output = run(dir.path(), &["-i", &database, "--tar", "-", "--out-html", "site"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let archive = String::from_utf8_lossy(&output.stdout);
+    assert!(archive.contains("site/general.html"));
+    assert!(archive.contains("general-2025-12.md"));
+    assert!(!dir.path().join("site").exists());
+}
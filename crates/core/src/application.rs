@@ -1,9 +1,174 @@
+use crate::domain::{ExtractedRecord, ExtractionReport};
 use crate::ports::{DataRepository, MarkdownWriter, Result};
+use crate::transforms::RecordTransform;
+use crate::utils::{
+    content_hash, count_links, extract_date_in, extract_year_month, format_epoch_millis_utc, snowflake_timestamp,
+    timestamp_to_epoch_millis, OutputTimezone,
+};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+/// What to do with records whose timestamp is empty or cannot be parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NullTimestampPolicy {
+    /// Keep them as-is; writers group them under `unknown`
+    #[default]
+    Bucket,
+    /// Drop them before writing
+    Drop,
+    /// Derive the timestamp from the record's snowflake message id when possible
+    Snowflake,
+    /// Carry over the timestamp of the closest preceding dated record
+    Inherit,
+}
+
+impl FromStr for NullTimestampPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "bucket" => Ok(Self::Bucket),
+            "drop" => Ok(Self::Drop),
+            "snowflake" => Ok(Self::Snowflake),
+            "inherit" => Ok(Self::Inherit),
+            other => Err(format!(
+                "unknown null-timestamp policy '{}' (expected bucket, drop, snowflake or inherit)",
+                other
+            )),
+        }
+    }
+}
+
+/// Which records to keep based on whether their channel is a thread
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThreadFilter {
+    /// Keep thread and main-channel messages
+    #[default]
+    All,
+    /// Keep only messages posted in threads
+    OnlyThreads,
+    /// Keep only messages posted outside threads
+    NoThreads,
+}
+
+impl ThreadFilter {
+    fn keeps(self, record: &ExtractedRecord) -> bool {
+        match self {
+            Self::All => true,
+            Self::OnlyThreads => record.is_thread,
+            Self::NoThreads => !record.is_thread,
+        }
+    }
+}
+
+/// A set of channels, by name, ignoring case
+/// Patterns containing glob metacharacters (`*`, `?`, `[`, `{`) are matched as globs,
+/// anything else must equal the channel name exactly
+#[derive(Debug, Clone)]
+pub struct ChannelFilter {
+    exact: HashSet<String>,
+    globs: GlobSet,
+}
+
+impl ChannelFilter {
+    pub fn new(patterns: &[String]) -> std::result::Result<Self, String> {
+        let mut exact = HashSet::new();
+        let mut globs = GlobSetBuilder::new();
+        for pattern in patterns {
+            if pattern.contains(['*', '?', '[', '{']) {
+                let glob = GlobBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("invalid channel pattern '{}': {}", pattern, e))?;
+                globs.add(glob);
+            } else {
+                exact.insert(pattern.to_lowercase());
+            }
+        }
+        let globs = globs.build().map_err(|e| e.to_string())?;
+        Ok(Self { exact, globs })
+    }
+
+    pub fn matches(&self, channel_name: &str) -> bool {
+        self.exact.contains(&channel_name.to_lowercase()) || self.globs.is_match(channel_name)
+    }
+}
+
+/// Most unparseable timestamps quoted in a `strict_timestamps` error
+const STRICT_TIMESTAMP_EXAMPLES: usize = 5;
+
+/// Options controlling which records reach the writers
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionOptions {
+    /// Drop records flagged as deleted by the source
+    pub exclude_deleted: bool,
+    /// Handling of records without a usable timestamp
+    pub null_timestamp_policy: NullTimestampPolicy,
+    /// Fail the extraction if any non-empty timestamp cannot be parsed, instead of
+    /// handling it like a missing one through `null_timestamp_policy`
+    pub strict_timestamps: bool,
+    /// Restrict records to thread or main-channel messages
+    pub threads: ThreadFilter,
+    /// Keep only records with at least one attachment
+    pub attachments_only: bool,
+    /// Drop records that are forwards or crossposts
+    pub exclude_forwards: bool,
+    /// Keep only records whose content contains at least one http(s) link
+    pub links_only: bool,
+    /// Keep only records whose reaction counts add up to at least this many
+    pub min_reactions: Option<u64>,
+    /// Keep only pinned records
+    pub pinned_only: bool,
+    /// Drop every record whose username or user id is listed, before any output or statistic
+    pub blocked_users: HashSet<String>,
+    /// Keep only authors with at least this many records after the other filters
+    pub min_author_messages: Option<usize>,
+    /// Keep only authors with at most this many records after the other filters
+    pub max_author_messages: Option<usize>,
+    /// Timezone in which `date`, `from` and `to` days are taken and snowflake timestamps are shown
+    pub timezone: OutputTimezone,
+    /// Keep only records from this calendar day (`YYYY-MM-DD`) in the output timezone
+    /// (`--timezone`, default local)
+    pub date: Option<String>,
+    /// Keep only records from this calendar day (`YYYY-MM-DD`) or later in the output timezone;
+    /// records with an unparseable timestamp are dropped whenever `from` or `to` is set
+    pub from: Option<String>,
+    /// Keep only records from this calendar day (`YYYY-MM-DD`) or earlier in the output timezone
+    pub to: Option<String>,
+    /// Keep only records strictly newer than this timestamp, e.g. the previous run's latest;
+    /// compared to the millisecond, using the stored timestamp when it parses
+    pub since: Option<String>,
+    /// Keep only records from channels matching this filter
+    pub channels: Option<ChannelFilter>,
+    /// Drop records from channels matching this filter, after `channels` has been applied
+    pub exclude_channels: Option<ChannelFilter>,
+    /// Number records within their channel, in order, across the whole export
+    pub number_messages: bool,
+    /// Apply Unicode NFC normalization to channel names and content before anything else,
+    /// so visually identical names group together and identical content dedups
+    pub normalize_unicode: bool,
+    /// Flag records whose normalized content also appears in another record
+    pub flag_duplicates: bool,
+    /// Drop records repeating an earlier one's channel, author, timestamp and content exactly,
+    /// e.g. from overlapping merged sources
+    pub dedupe: bool,
+    /// Detect each record's content language before filtering, setting `language`
+    pub detect_language: bool,
+    /// Keep only records detected as this language (ISO 639-3 code, e.g. `spa`); implies detection
+    pub language: Option<String>,
+    /// Fetch and write one channel at a time, bounding memory to the largest channel
+    /// Writers are called once per channel, so they must not need the whole export at once
+    pub per_channel_fetch: bool,
+}
 
 /// Application service for extracting and formatting Discord messages
 pub struct ExtractionServiceImpl {
     data_repository: Box<dyn DataRepository>,
-    markdown_writer: Box<dyn MarkdownWriter>,
+    writers: Vec<Box<dyn MarkdownWriter>>,
+    transforms: Vec<Box<dyn RecordTransform>>,
+    options: ExtractionOptions,
 }
 
 impl ExtractionServiceImpl {
@@ -14,15 +179,939 @@ impl ExtractionServiceImpl {
     ) -> Self {
         Self {
             data_repository,
-            markdown_writer,
+            writers: vec![markdown_writer],
+            transforms: Vec::new(),
+            options: ExtractionOptions::default(),
         }
     }
 
+    /// Adds a secondary writer that receives the same records as the main writer
+    pub fn with_writer(mut self, writer: Box<dyn MarkdownWriter>) -> Self {
+        self.writers.push(writer);
+        self
+    }
+
+    /// Adds a transform applied to every record after filtering, in registration order
+    pub fn with_transform(mut self, transform: Box<dyn RecordTransform>) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Sets the options used to filter records before writing
+    pub fn with_options(mut self, options: ExtractionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Executes the extraction process: fetches records and writes them as markdown
-    pub fn execute_extraction(&self) -> Result<()> {
-        let records = self.data_repository.fetch_all_records()?;
-        self.markdown_writer.write(&records)?;
-        Ok(())
+    /// Returns a report of what was written and what transforms observed
+    pub fn execute_extraction(&self) -> Result<ExtractionReport> {
+        let mut report = ExtractionReport::default();
+        let latest = if self.options.per_channel_fetch {
+            let mut latest = None;
+            for channel_id in self.data_repository.fetch_channel_ids()? {
+                let records = self.data_repository.fetch_by_channel(&channel_id)?;
+                log::debug!(channel = channel_id.as_str(), count = records.len(); "fetched channel records");
+                latest = latest.max(self.process_and_write(records, &mut report)?);
+            }
+            latest
+        } else {
+            let records = self.data_repository.fetch_all_records()?;
+            log::info!(count = records.len(); "fetched records");
+            self.process_and_write(records, &mut report)?
+        };
+
+        log::info!(count = report.records_written, writers = self.writers.len(); "wrote records");
+
+        report.latest_timestamp = latest.map(format_epoch_millis_utc);
+        for transform in &self.transforms {
+            transform.report(&mut report);
+        }
+        Ok(report)
     }
+
+    /// Runs fetched records through the policy, filters and transforms, then hands them to
+    /// every writer; counts what was written and dropped into `report` and returns the newest
+    /// written record's epoch milliseconds
+    fn process_and_write(&self, mut records: Vec<ExtractedRecord>, report: &mut ExtractionReport) -> Result<Option<i64>> {
+        if self.options.normalize_unicode {
+            records.iter_mut().for_each(normalize_unicode);
+        }
+        if self.options.strict_timestamps {
+            check_timestamps(&records)?;
+        }
+        let mut records = self.apply_null_timestamp_policy(records);
+        if self.options.dedupe {
+            let fetched = records.len();
+            records = dedupe_records(records);
+            report.duplicates_removed += fetched - records.len();
+        }
+        if self.options.detect_language || self.options.language.is_some() {
+            records.iter_mut().for_each(detect_language);
+        }
+        let mut records = self.filter_records(records);
+        if self.options.min_author_messages.is_some() || self.options.max_author_messages.is_some() {
+            records = filter_by_author_volume(records, self.options.min_author_messages, self.options.max_author_messages);
+        }
+        if self.options.flag_duplicates {
+            flag_duplicates(&mut records);
+        }
+        for record in &mut records {
+            for transform in &self.transforms {
+                transform.apply(record);
+            }
+        }
+        if self.options.number_messages {
+            number_by_channel(&mut records);
+        }
+        for writer in &self.writers {
+            writer.write(&records)?;
+        }
+        report.records_written += records.len();
+        Ok(records.iter().filter_map(ExtractedRecord::epoch_millis).max())
+    }
+
+    /// Fixes up or drops records whose timestamp has no month, per the configured policy
+    fn apply_null_timestamp_policy(&self, records: Vec<ExtractedRecord>) -> Vec<ExtractedRecord> {
+        let is_undated = |record: &ExtractedRecord| extract_year_month(&record.timestamp).is_none();
+        let mut last_dated: Option<String> = None;
+
+        records
+            .into_iter()
+            .filter_map(|mut record| {
+                if !is_undated(&record) {
+                    last_dated = Some(record.timestamp.clone());
+                    return Some(record);
+                }
+                match self.options.null_timestamp_policy {
+                    NullTimestampPolicy::Bucket => {}
+                    NullTimestampPolicy::Drop => return None,
+                    NullTimestampPolicy::Snowflake => {
+                        let timezone = self.options.timezone;
+                        let timestamp = record.message_id.as_deref().and_then(|id| snowflake_timestamp(id, timezone));
+                        if let Some(timestamp) = timestamp {
+                            record.timestamp = timestamp;
+                        }
+                    }
+                    NullTimestampPolicy::Inherit => {
+                        if let Some(timestamp) = &last_dated {
+                            record.timestamp = timestamp.clone();
+                        }
+                    }
+                }
+                Some(record)
+            })
+            .collect()
+    }
+
+    /// Whether a record's author is blocked by username or user id
+    fn is_blocked(&self, record: &ExtractedRecord) -> bool {
+        let blocked = &self.options.blocked_users;
+        blocked.contains(&record.username) || record.user_id.as_ref().is_some_and(|id| blocked.contains(id))
+    }
+
+    /// Whether the record's day in the output timezone lies within the inclusive `from`..`to` range
+    /// Days are `YYYY-MM-DD`, so comparing them as strings orders them chronologically
+    fn in_date_range(&self, record: &ExtractedRecord) -> bool {
+        let (from, to) = (self.options.from.as_deref(), self.options.to.as_deref());
+        if from.is_none() && to.is_none() {
+            return true;
+        }
+        extract_date_in(&record.timestamp, self.options.timezone).is_some_and(|day| {
+            from.is_none_or(|from| day.as_str() >= from) && to.is_none_or(|to| day.as_str() <= to)
+        })
+    }
+
+    /// Applies the configured filters, preserving record order
+    fn filter_records(&self, records: Vec<ExtractedRecord>) -> Vec<ExtractedRecord> {
+        let since = self.options.since.as_deref().and_then(timestamp_to_epoch_millis);
+        records
+            .into_iter()
+            .filter(|record| !self.is_blocked(record))
+            .filter(|record| !(self.options.exclude_deleted && record.deleted))
+            .filter(|record| self.options.threads.keeps(record))
+            .filter(|record| !(self.options.attachments_only && record.attachments.is_empty()))
+            .filter(|record| !(self.options.exclude_forwards && record.forwarded))
+            .filter(|record| !(self.options.links_only && count_links(&record.content) == 0))
+            .filter(|record| self.options.min_reactions.is_none_or(|min| record.reaction_total() >= min))
+            .filter(|record| !self.options.pinned_only || record.pinned)
+            .filter(|record| {
+                let date = self.options.date.as_deref();
+                let day = |record: &ExtractedRecord| extract_date_in(&record.timestamp, self.options.timezone);
+                date.is_none_or(|date| day(record).as_deref() == Some(date))
+            })
+            .filter(|record| self.in_date_range(record))
+            .filter(|record| {
+                let language = self.options.language.as_deref();
+                language.is_none_or(|language| record.language.as_deref() == Some(language))
+            })
+            .filter(|record| since.is_none_or(|since| record.epoch_millis().is_some_and(|epoch| epoch > since)))
+            .filter(|record| self.options.channels.as_ref().is_none_or(|channels| channels.matches(&record.channel_name)))
+            .filter(|record| {
+                let excluded = self.options.exclude_channels.as_ref();
+                excluded.is_none_or(|excluded| !excluded.matches(&record.channel_name))
+            })
+            .collect()
+    }
+}
+
+/// Assigns each record its 1-based position within its channel, following record order
+fn number_by_channel(records: &mut [ExtractedRecord]) {
+    let mut counters: HashMap<String, usize> = HashMap::new();
+    for record in records {
+        let counter = counters.entry(record.channel_name.clone()).or_default();
+        *counter += 1;
+        record.channel_index = Some(*counter);
+    }
+}
+
+/// Drops the records of authors whose record count falls outside `min..=max`
+/// Needs every record up front, as an author's total is only known after a full pass
+fn filter_by_author_volume(
+    records: Vec<ExtractedRecord>,
+    min: Option<usize>,
+    max: Option<usize>,
+) -> Vec<ExtractedRecord> {
+    let mut totals: HashMap<String, usize> = HashMap::new();
+    for record in &records {
+        *totals.entry(record.username.clone()).or_default() += 1;
+    }
+    records
+        .into_iter()
+        .filter(|record| {
+            let total = totals[&record.username];
+            min.is_none_or(|min| total >= min) && max.is_none_or(|max| total <= max)
+        })
+        .collect()
+}
+
+/// Errors if any record has a non-empty timestamp without a parseable month,
+/// quoting the first few offending values as stored in the source
+fn check_timestamps(records: &[ExtractedRecord]) -> Result<()> {
+    let invalid: Vec<&ExtractedRecord> = records
+        .iter()
+        .filter(|record| !record.timestamp.trim().is_empty() && extract_year_month(&record.timestamp).is_none())
+        .collect();
+    if invalid.is_empty() {
+        return Ok(());
+    }
+
+    let examples: Vec<String> = invalid
+        .iter()
+        .take(STRICT_TIMESTAMP_EXAMPLES)
+        .map(|record| {
+            let raw = if record.raw_timestamp.is_empty() { &record.timestamp } else { &record.raw_timestamp };
+            format!("'{}'", raw)
+        })
+        .collect();
+    Err(format!("{} records have unparseable timestamps, e.g. {}", invalid.len(), examples.join(", ")).into())
 }
 
+/// Rewrites a record's channel name and content in Unicode Normalization Form C
+fn normalize_unicode(record: &mut ExtractedRecord) {
+    record.channel_name = record.channel_name.nfc().collect();
+    record.content = record.content.nfc().collect();
+}
+
+/// Sets a record's language to the ISO 639-3 code whatlang detects in its content, or None
+/// when the content is too short or mixed for a reliable guess
+fn detect_language(record: &mut ExtractedRecord) {
+    record.language = whatlang::detect(&record.content)
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang().code().to_string());
+}
+
+/// Drops records whose channel, author, timestamp and content all equal an earlier record's,
+/// keeping the first of each so the order is unchanged. Records differing in any of these,
+/// such as the same text posted at another time, are all kept
+pub fn dedupe_records(records: Vec<ExtractedRecord>) -> Vec<ExtractedRecord> {
+    let mut seen = HashSet::new();
+    records
+        .into_iter()
+        .filter(|record| {
+            seen.insert((
+                record.channel_name.clone(),
+                record.username.clone(),
+                record.timestamp.clone(),
+                record.content.clone(),
+            ))
+        })
+        .collect()
+}
+
+/// Marks every record whose non-empty content, once normalized, is shared with another record
+/// Content is compared before transforms run, so redaction cannot create false duplicates
+fn flag_duplicates(records: &mut [ExtractedRecord]) {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    let hashes: Vec<Option<u64>> = records
+        .iter()
+        .map(|record| (!record.content.trim().is_empty()).then(|| content_hash(&record.content)))
+        .collect();
+    for hash in hashes.iter().flatten() {
+        *counts.entry(*hash).or_default() += 1;
+    }
+    for (record, hash) in records.iter_mut().zip(hashes) {
+        record.duplicate = hash.is_some_and(|hash| counts[&hash] > 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Reaction;
+    use std::sync::{Arc, Mutex};
+
+    struct FakeRepository {
+        records: Vec<ExtractedRecord>,
+    }
+
+    impl DataRepository for FakeRepository {
+        fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+            Ok(self.records.clone())
+        }
+    }
+
+    /// Captures the records handed to the writer for inspection
+    #[derive(Clone, Default)]
+    struct CapturingWriter {
+        written: Arc<Mutex<Vec<ExtractedRecord>>>,
+    }
+
+    impl MarkdownWriter for CapturingWriter {
+        fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+            self.written.lock().unwrap().extend_from_slice(records);
+            Ok(())
+        }
+    }
+
+    fn record(content: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: "general".to_string(),
+            username: "alice".to_string(),
+            timestamp: "2025-12-16 10:30:00".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Runs the service over the given records and returns the contents that were written
+    fn run(records: Vec<ExtractedRecord>, options: ExtractionOptions) -> Vec<String> {
+        run_service(records, |service| service.with_options(options))
+    }
+
+    /// Runs a service customised by `configure` and returns the contents that were written
+    fn run_service(
+        records: Vec<ExtractedRecord>,
+        configure: impl FnOnce(ExtractionServiceImpl) -> ExtractionServiceImpl,
+    ) -> Vec<String> {
+        let writer = CapturingWriter::default();
+        let service = configure(ExtractionServiceImpl::new(
+            Box::new(FakeRepository { records }),
+            Box::new(writer.clone()),
+        ));
+
+        service.execute_extraction().unwrap();
+
+        let written = writer.written.lock().unwrap();
+        written.iter().map(|r| r.content.clone()).collect()
+    }
+
+    fn deleted(content: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            deleted: true,
+            ..record(content)
+        }
+    }
+
+    #[test]
+    fn test_deleted_records_kept_by_default() {
+        let written = run(vec![record("a"), deleted("b")], ExtractionOptions::default());
+        assert_eq!(written, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_exclude_deleted_drops_deleted_records() {
+        let options = ExtractionOptions {
+            exclude_deleted: true,
+            ..Default::default()
+        };
+        let written = run(vec![record("a"), deleted("b"), record("c")], options);
+        assert_eq!(written, vec!["a", "c"]);
+    }
+
+    fn undated(content: &str, message_id: Option<&str>) -> ExtractedRecord {
+        ExtractedRecord {
+            timestamp: String::new(),
+            message_id: message_id.map(str::to_string),
+            ..record(content)
+        }
+    }
+
+    /// Runs the service with the given null-timestamp policy and returns (content, timestamp) pairs
+    fn run_policy(records: Vec<ExtractedRecord>, policy: NullTimestampPolicy) -> Vec<(String, String)> {
+        let writer = CapturingWriter::default();
+        let service = ExtractionServiceImpl::new(
+            Box::new(FakeRepository { records }),
+            Box::new(writer.clone()),
+        )
+        .with_options(ExtractionOptions {
+            null_timestamp_policy: policy,
+            ..Default::default()
+        });
+
+        service.execute_extraction().unwrap();
+
+        let written = writer.written.lock().unwrap();
+        written.iter().map(|r| (r.content.clone(), r.timestamp.clone())).collect()
+    }
+
+    #[test]
+    fn test_null_timestamp_policy_drop() {
+        let written = run_policy(
+            vec![record("a"), undated("b", None), record("c")],
+            NullTimestampPolicy::Drop,
+        );
+        let contents: Vec<&str> = written.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(contents, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_null_timestamp_policy_snowflake() {
+        let written = run_policy(
+            vec![undated("a", Some("175928847299117063")), undated("b", Some("not-a-snowflake"))],
+            NullTimestampPolicy::Snowflake,
+        );
+        assert_eq!(extract_year_month(&written[0].1), Some("2016-04".to_string()));
+        assert_eq!(written[1].1, "");
+    }
+
+    #[test]
+    fn test_null_timestamp_policy_inherit() {
+        let written = run_policy(
+            vec![undated("a", None), record("b"), undated("c", None)],
+            NullTimestampPolicy::Inherit,
+        );
+        assert_eq!(written[0].1, "");
+        assert_eq!(written[2].1, "2025-12-16 10:30:00");
+    }
+
+    #[test]
+    fn test_null_timestamp_policy_from_str() {
+        assert_eq!("bucket".parse(), Ok(NullTimestampPolicy::Bucket));
+        assert_eq!("inherit".parse(), Ok(NullTimestampPolicy::Inherit));
+        assert!("sometimes".parse::<NullTimestampPolicy>().is_err());
+    }
+
+    fn thread_fixture() -> Vec<ExtractedRecord> {
+        let in_thread = |content: &str| ExtractedRecord {
+            is_thread: true,
+            ..record(content)
+        };
+        vec![record("main 1"), in_thread("thread 1"), record("main 2"), in_thread("thread 2")]
+    }
+
+    #[test]
+    fn test_only_threads_keeps_thread_messages() {
+        let options = ExtractionOptions {
+            threads: ThreadFilter::OnlyThreads,
+            ..Default::default()
+        };
+        assert_eq!(run(thread_fixture(), options), vec!["thread 1", "thread 2"]);
+    }
+
+    #[test]
+    fn test_no_threads_keeps_main_channel_messages() {
+        let options = ExtractionOptions {
+            threads: ThreadFilter::NoThreads,
+            ..Default::default()
+        };
+        assert_eq!(run(thread_fixture(), options), vec!["main 1", "main 2"]);
+    }
+
+    #[test]
+    fn test_attachments_only_keeps_records_with_attachments() {
+        let with_attachment = |content: &str| ExtractedRecord {
+            attachments: vec!["https://cdn.example.com/image.png".to_string()],
+            ..record(content)
+        };
+        let options = ExtractionOptions {
+            attachments_only: true,
+            exclude_deleted: true,
+            ..Default::default()
+        };
+        let records = vec![
+            record("text"),
+            with_attachment("photo"),
+            ExtractedRecord {
+                deleted: true,
+                ..with_attachment("deleted photo")
+            },
+            with_attachment("video"),
+        ];
+
+        assert_eq!(run(records, options), vec!["photo", "video"]);
+    }
+
+    #[test]
+    fn test_links_only_keeps_records_with_urls() {
+        let options = ExtractionOptions {
+            links_only: true,
+            ..Default::default()
+        };
+        let records = vec![
+            record("see https://example.com"),
+            record("no link, just http talk"),
+            record("two: http://a.io https://cdn.discordapp.com/x.png"),
+        ];
+
+        assert_eq!(
+            run(records, options),
+            vec!["see https://example.com", "two: http://a.io https://cdn.discordapp.com/x.png"]
+        );
+    }
+
+    #[test]
+    fn test_exclude_forwards_drops_forwarded_records() {
+        let forwarded = ExtractedRecord {
+            forwarded: true,
+            ..record("forwarded")
+        };
+        let records = vec![record("a"), forwarded, record("b")];
+
+        assert_eq!(run(records.clone(), ExtractionOptions::default()), vec!["a", "forwarded", "b"]);
+        let options = ExtractionOptions {
+            exclude_forwards: true,
+            ..Default::default()
+        };
+        assert_eq!(run(records, options), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_number_messages_counts_per_channel_after_filtering() {
+        let writer = CapturingWriter::default();
+        let mut random = record("r1");
+        random.channel_name = "random".to_string();
+        let service = ExtractionServiceImpl::new(
+            Box::new(FakeRepository {
+                records: vec![record("g1"), deleted("gone"), random, record("g2")],
+            }),
+            Box::new(writer.clone()),
+        )
+        .with_options(ExtractionOptions {
+            number_messages: true,
+            exclude_deleted: true,
+            ..Default::default()
+        });
+
+        service.execute_extraction().unwrap();
+
+        let written = writer.written.lock().unwrap();
+        let numbers: Vec<(&str, Option<usize>)> =
+            written.iter().map(|r| (r.content.as_str(), r.channel_index)).collect();
+        assert_eq!(numbers, vec![("g1", Some(1)), ("r1", Some(1)), ("g2", Some(2))]);
+    }
+
+    struct Suffix(&'static str);
+
+    impl RecordTransform for Suffix {
+        fn apply(&self, record: &mut ExtractedRecord) {
+            record.content.push_str(self.0);
+        }
+    }
+
+    #[test]
+    fn test_transforms_apply_in_registration_order() {
+        let written = run_service(vec![record("a"), record("b")], |service| {
+            service
+                .with_transform(Box::new(Suffix("1")))
+                .with_transform(Box::new(Suffix("2")))
+        });
+        assert_eq!(written, vec!["a12", "b12"]);
+    }
+
+    #[test]
+    fn test_report_lists_unresolved_mentions() {
+        let writer = CapturingWriter::default();
+        let usernames = std::collections::HashMap::from([("1".to_string(), "alice".to_string())]);
+        let service = ExtractionServiceImpl::new(
+            Box::new(FakeRepository {
+                records: vec![record("hi <@1>"), record("who is <@42>?")],
+            }),
+            Box::new(writer.clone()),
+        )
+        .with_transform(Box::new(crate::transforms::ResolveMentions::new(usernames)));
+
+        let report = service.execute_extraction().unwrap();
+
+        assert_eq!(report.records_written, 2);
+        assert_eq!(report.unresolved_mentions.into_iter().collect::<Vec<_>>(), vec!["42"]);
+        let written = writer.written.lock().unwrap();
+        assert_eq!(written[0].content, "hi @alice");
+    }
+
+    #[test]
+    fn test_strict_timestamps_rejects_unparseable_values() {
+        let at = |timestamp: &str| ExtractedRecord {
+            timestamp: timestamp.to_string(),
+            raw_timestamp: timestamp.to_string(),
+            ..record(timestamp)
+        };
+        let execute = |records: Vec<ExtractedRecord>| {
+            ExtractionServiceImpl::new(Box::new(FakeRepository { records }), Box::new(CapturingWriter::default()))
+                .with_options(ExtractionOptions {
+                    strict_timestamps: true,
+                    ..Default::default()
+                })
+                .execute_extraction()
+        };
+
+        let valid = vec![at("2025-12-16 10:30:00"), at("")];
+        assert!(execute(valid.clone()).is_ok());
+
+        let mut invalid = valid;
+        invalid.extend([at("yesterday"), at("n/a")]);
+        let error = execute(invalid).unwrap_err().to_string();
+        assert_eq!(error, "2 records have unparseable timestamps, e.g. 'yesterday', 'n/a'");
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_date_keeps_records_of_that_local_day() {
+        use chrono::{Local, TimeZone};
+
+        // Timestamps carry explicit offsets, so the local day boundary is what decides
+        let at_local = |day: u32, hour: u32, minute: u32, second: u32| {
+            let local = Local.with_ymd_and_hms(2025, 12, day, hour, minute, second).unwrap();
+            ExtractedRecord {
+                timestamp: local.to_rfc3339(),
+                ..record(&format!("{} {:02}:{:02}", day, hour, minute))
+            }
+        };
+        let records = vec![
+            at_local(15, 23, 59, 59),
+            at_local(16, 0, 0, 0),
+            at_local(16, 23, 59, 59),
+            at_local(17, 0, 0, 0),
+        ];
+        let options = ExtractionOptions {
+            date: Some("2025-12-16".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(run(records, options), vec!["16 00:00", "16 23:59"]);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_from_to_keeps_records_of_the_inclusive_day_range() {
+        use chrono::{Local, TimeZone};
+
+        let at_local = |day: u32, hour: u32| {
+            let local = Local.with_ymd_and_hms(2025, 12, day, hour, 0, 0).unwrap();
+            ExtractedRecord {
+                timestamp: local.to_rfc3339(),
+                ..record(&format!("{} {:02}h", day, hour))
+            }
+        };
+        let unparseable = ExtractedRecord {
+            timestamp: "n/a".to_string(),
+            ..record("undated")
+        };
+        let records = vec![at_local(9, 23), at_local(10, 0), at_local(14, 12), at_local(16, 23), at_local(17, 0), unparseable];
+        let range = |from: Option<&str>, to: Option<&str>| ExtractionOptions {
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            run(records.clone(), range(Some("2025-12-10"), Some("2025-12-16"))),
+            vec!["10 00h", "14 12h", "16 23h"]
+        );
+        assert_eq!(run(records.clone(), range(Some("2025-12-16"), None)), vec!["16 23h", "17 00h"]);
+        assert_eq!(run(records.clone(), range(None, Some("2025-12-09"))), vec!["9 23h"]);
+        assert_eq!(run(records, range(None, None)).len(), 6);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_date_is_taken_in_the_configured_timezone() {
+        let records = vec![ExtractedRecord {
+            timestamp: "2025-12-01T02:30:00Z".to_string(),
+            ..record("late evening in New York")
+        }];
+        let on_day = |timezone: &str| ExtractionOptions {
+            timezone: timezone.parse().unwrap(),
+            date: Some("2025-11-30".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(run(records.clone(), on_day("America/New_York")), vec!["late evening in New York"]);
+        assert!(run(records, on_day("UTC")).is_empty());
+    }
+
+    #[test]
+    fn test_min_reactions_keeps_popular_records() {
+        let reacted = |content: &str, counts: &[u64]| ExtractedRecord {
+            reactions: counts
+                .iter()
+                .map(|&count| Reaction {
+                    emoji: "👍".to_string(),
+                    count,
+                })
+                .collect(),
+            ..record(content)
+        };
+        let records = vec![
+            reacted("none", &[]),
+            reacted("few", &[2, 2]),
+            reacted("exact", &[3, 2]),
+            reacted("many", &[9]),
+        ];
+        let options = |pinned_only: bool| ExtractionOptions {
+            min_reactions: Some(5),
+            pinned_only,
+            ..Default::default()
+        };
+
+        assert_eq!(run(records.clone(), options(false)), vec!["exact", "many"]);
+
+        let mut pinned = records;
+        pinned[3].pinned = true;
+        assert_eq!(run(pinned, options(true)), vec!["many"]);
+    }
+
+    #[test]
+    fn test_blocked_users_are_absent_from_every_output() {
+        let by = |username: &str, user_id: &str, content: &str| ExtractedRecord {
+            username: username.to_string(),
+            user_id: Some(user_id.to_string()),
+            ..record(content)
+        };
+        let records = vec![
+            by("alice", "1", "hello"),
+            by("mallory", "2", "remove me"),
+            by("bob", "3", "hi"),
+            by("mallory-renamed", "2", "me too"),
+            by("carol", "4", "hey"),
+        ];
+        let writer = CapturingWriter::default();
+        let service = ExtractionServiceImpl::new(Box::new(FakeRepository { records }), Box::new(writer.clone()))
+            .with_options(ExtractionOptions {
+                blocked_users: HashSet::from(["2".to_string(), "carol".to_string()]),
+                ..Default::default()
+            });
+
+        let report = service.execute_extraction().unwrap();
+
+        let written = writer.written.lock().unwrap();
+        let contents: Vec<&str> = written.iter().map(|record| record.content.as_str()).collect();
+        assert_eq!(contents, vec!["hello", "hi"]);
+        assert_eq!(report.records_written, 2);
+        let roster: Vec<String> = crate::roster::build_roster(&written).into_iter().map(|entry| entry.username).collect();
+        assert_eq!(roster, vec!["alice", "bob"]);
+        let summary = crate::summary::build_summary(&written);
+        assert_eq!(summary.total_messages, 2);
+        assert_eq!(summary.authors.keys().collect::<Vec<_>>(), vec!["alice", "bob"]);
+    }
+
+    #[test]
+    fn test_author_message_range_drops_lurkers_and_spammers() {
+        let by = |username: &str, content: &str| ExtractedRecord {
+            username: username.to_string(),
+            ..record(content)
+        };
+        let records = vec![
+            by("spammer", "s1"),
+            by("lurker", "l1"),
+            by("regular", "r1"),
+            by("spammer", "s2"),
+            by("regular", "r2"),
+            by("spammer", "s3"),
+            by("spammer", "s4"),
+        ];
+        let options = ExtractionOptions {
+            min_author_messages: Some(2),
+            max_author_messages: Some(3),
+            ..Default::default()
+        };
+
+        assert_eq!(run(records, options), vec!["r1", "r2"]);
+    }
+
+    #[test]
+    fn test_language_detection_and_filter() {
+        let records = vec![
+            record("The weather is lovely today and we are going to the park together."),
+            record("¿Dónde está la biblioteca? Necesito encontrar un libro sobre la historia de España para mañana."),
+            record("ok"),
+        ];
+        let detect = |language: Option<&str>| {
+            let writer = CapturingWriter::default();
+            ExtractionServiceImpl::new(Box::new(FakeRepository { records: records.clone() }), Box::new(writer.clone()))
+                .with_options(ExtractionOptions {
+                    detect_language: true,
+                    language: language.map(str::to_string),
+                    ..Default::default()
+                })
+                .execute_extraction()
+                .unwrap();
+            let written = writer.written.lock().unwrap();
+            written.iter().map(|record| record.language.clone()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(detect(None), vec![Some("eng".to_string()), Some("spa".to_string()), None]);
+        assert_eq!(detect(Some("spa")), vec![Some("spa".to_string())]);
+    }
+
+    #[test]
+    fn test_dedupe_records_keeps_first_of_exact_duplicates() {
+        let at = |timestamp: &str, content: &str| ExtractedRecord {
+            timestamp: timestamp.to_string(),
+            ..record(content)
+        };
+        let records = vec![
+            at("2025-12-16 10:00:00", "hello"),
+            at("2025-12-16 10:05:00", "world"),
+            at("2025-12-16 10:00:00", "hello"),
+            at("2025-12-16 10:05:00", "world"),
+        ];
+
+        assert_eq!(dedupe_records(records.clone()), records[..2].to_vec());
+    }
+
+    #[test]
+    fn test_dedupe_records_keeps_near_duplicates() {
+        let at = |timestamp: &str, username: &str| ExtractedRecord {
+            timestamp: timestamp.to_string(),
+            username: username.to_string(),
+            ..record("+1")
+        };
+        let records = vec![
+            at("2025-12-16 10:00:00", "alice"),
+            at("2025-12-16 10:01:00", "alice"),
+            at("2025-12-16 10:00:00", "bob"),
+        ];
+
+        assert_eq!(dedupe_records(records.clone()), records);
+    }
+
+    #[test]
+    fn test_dedupe_reports_removed_count() {
+        let records = vec![record("a"), record("b"), record("a"), record("a")];
+        let service = ExtractionServiceImpl::new(Box::new(FakeRepository { records }), Box::new(CapturingWriter::default()))
+            .with_options(ExtractionOptions {
+                dedupe: true,
+                ..Default::default()
+            });
+
+        let report = service.execute_extraction().unwrap();
+
+        assert_eq!((report.records_written, report.duplicates_removed), (2, 2));
+    }
+
+    #[test]
+    fn test_flag_duplicates_marks_repeated_content() {
+        let records = vec![record("Buy cheap coins!"), record("hello"), record("  buy  CHEAP coins! ")];
+        let writer = CapturingWriter::default();
+        let service = ExtractionServiceImpl::new(Box::new(FakeRepository { records }), Box::new(writer.clone()))
+            .with_options(ExtractionOptions {
+                flag_duplicates: true,
+                ..Default::default()
+            });
+
+        service.execute_extraction().unwrap();
+
+        let written = writer.written.lock().unwrap();
+        let flags: Vec<bool> = written.iter().map(|record| record.duplicate).collect();
+        assert_eq!(flags, vec![true, false, true]);
+        let summary = crate::summary::build_summary(&written);
+        assert_eq!((summary.duplicate_clusters, summary.duplicate_messages), (1, 2));
+    }
+
+    #[test]
+    fn test_channel_filter_matches_globs_and_exact_names() {
+        let names = ["team-a", "team-bb", "teams", "general", "gen-1", "gen-12"];
+        let matching = |patterns: &[&str]| {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            let filter = ChannelFilter::new(&patterns).unwrap();
+            names.iter().copied().filter(|name| filter.matches(name)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching(&["team-*"]), vec!["team-a", "team-bb"]);
+        assert_eq!(matching(&["gen-?"]), vec!["gen-1"]);
+        assert_eq!(matching(&["general", "gen-??"]), vec!["general", "gen-12"]);
+        assert_eq!(matching(&["team"]), Vec::<&str>::new());
+        assert_eq!(matching(&["GENERAL", "Team-B*"]), vec!["team-bb", "general"]);
+        assert!(ChannelFilter::new(&["team-[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_channel_filter_drops_other_channels() {
+        let in_channel = |channel: &str| ExtractedRecord {
+            channel_name: channel.to_string(),
+            ..record(channel)
+        };
+        let options = ExtractionOptions {
+            channels: Some(ChannelFilter::new(&["project-*".to_string()]).unwrap()),
+            ..Default::default()
+        };
+
+        let written = run(vec![in_channel("project-x"), in_channel("general"), in_channel("project-y")], options);
+
+        assert_eq!(written, vec!["project-x", "project-y"]);
+    }
+
+    #[test]
+    fn test_include_and_exclude_channels() {
+        let in_channel = |channel: &str| ExtractedRecord {
+            channel_name: channel.to_string(),
+            ..record(channel)
+        };
+        let records = vec![in_channel("general"), in_channel("dev-ops"), in_channel("dev-web"), in_channel("random")];
+        let filter = |patterns: &[&str]| {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            (!patterns.is_empty()).then(|| ChannelFilter::new(&patterns).unwrap())
+        };
+        let keep = |include: &[&str], exclude: &[&str]| {
+            let options = ExtractionOptions {
+                channels: filter(include),
+                exclude_channels: filter(exclude),
+                ..Default::default()
+            };
+            run(records.clone(), options)
+        };
+
+        assert_eq!(keep(&["General", "DEV-*"], &[]), vec!["general", "dev-ops", "dev-web"]);
+        assert_eq!(keep(&[], &["Random", "dev-ops"]), vec!["general", "dev-web"]);
+        assert_eq!(keep(&["dev-*", "general"], &["DEV-WEB"]), vec!["general", "dev-ops"]);
+    }
+
+    #[test]
+    fn test_per_channel_fetch_writes_same_records_channel_by_channel() {
+        let in_channel = |channel: &str, content: &str| ExtractedRecord {
+            channel_name: channel.to_string(),
+            channel_id: Some(channel.to_string()),
+            ..record(content)
+        };
+        let records = vec![
+            in_channel("general", "g1"),
+            in_channel("random", "r1"),
+            in_channel("general", "g2"),
+            in_channel("random", "r2"),
+        ];
+        let run_mode = |per_channel_fetch: bool| {
+            let options = ExtractionOptions {
+                per_channel_fetch,
+                ..Default::default()
+            };
+            run(records.clone(), options)
+        };
+
+        // Same records in the same per-channel order; only the interleaving of channels differs
+        let mut all_at_once = run_mode(false);
+        all_at_once.sort_by_key(|content| content.starts_with('r'));
+        assert_eq!(run_mode(true), all_at_once);
+        assert_eq!(all_at_once, vec!["g1", "g2", "r1", "r2"]);
+    }
+}
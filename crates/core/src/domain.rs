@@ -1,8 +1,74 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ExtractedRecord {
     pub channel_name: String,
+    pub channel_id: Option<String>,
     pub username: String,
+    pub user_id: Option<String>,
     pub timestamp: String, // Treat as String for now
+    pub raw_timestamp: String, // timestamp exactly as stored in the source, before localization
     pub content: String,
+    pub message_id: Option<String>,
+    pub reply_to: Option<String>, // message_id of the referenced message
+    pub deleted: bool,
+    pub is_thread: bool, // channel is a thread (has a parent channel)
+    pub attachments: Vec<String>, // attachment URLs or markers, empty when none
+    pub channel_index: Option<usize>, // 1-based position within the channel across the export
+    pub category: Option<String>, // name of the channel's category, when the source has one
+    pub source: Option<String>, // path of the database the record was read from
+    pub reactions: Vec<Reaction>, // emoji reactions with their counts, empty when none
+    pub forwarded: bool, // message is a forward or crosspost of another message
+    pub duplicate: bool, // content repeats another message's, after normalization (see `flag_duplicates`)
+    pub pinned: bool, // message is pinned in its channel
+    pub language: Option<String>, // ISO 639-3 code of the detected content language (see `detect_language`)
+    pub jump_url: Option<String>, // link to the message in the original client, when the source knows it
+}
+
+/// One emoji reaction on a message, as stored by the crawler
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reaction {
+    pub emoji: String,
+    pub count: u64,
+}
+
+impl ExtractedRecord {
+    /// Total number of reactions on the message, across all emoji
+    pub fn reaction_total(&self) -> u64 {
+        self.reactions.iter().map(|reaction| reaction.count).sum()
+    }
+
+    /// Unix seconds of the message, parsed from the stored timestamp (or the shown one when the
+    /// source kept none); None when unparseable
+    pub fn epoch_seconds(&self) -> Option<i64> {
+        let source = if self.raw_timestamp.is_empty() {
+            &self.timestamp
+        } else {
+            &self.raw_timestamp
+        };
+        crate::utils::timestamp_to_epoch(source)
+    }
+
+    /// Unix milliseconds of the message, from the stored timestamp when it parses (it may carry
+    /// a fraction of a second the shown one lacks), otherwise from the shown one
+    pub fn epoch_millis(&self) -> Option<i64> {
+        crate::utils::timestamp_to_epoch_millis(&self.raw_timestamp)
+            .or_else(|| crate::utils::timestamp_to_epoch_millis(&self.timestamp))
+    }
 }
 
+/// Outcome of an extraction run, for reporting and debugging
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractionReport {
+    /// Number of records handed to the writers
+    pub records_written: usize,
+    /// User ids of mentions that could not be resolved to a username
+    pub unresolved_mentions: BTreeSet<String>,
+    /// Newest parseable timestamp among the written records, as an RFC 3339 UTC string with
+    /// milliseconds when it has any
+    pub latest_timestamp: Option<String>,
+    /// Exact duplicate records dropped before writing (see `ExtractionOptions::dedupe`)
+    pub duplicates_removed: usize,
+}
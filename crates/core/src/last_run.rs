@@ -0,0 +1,133 @@
+use crate::ports::{OutputSink, Result};
+use crate::sinks::DirectorySink;
+use crate::utils::timestamp_to_epoch;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// A state file holding the newest message timestamp exported by the previous run, so the next
+/// run can export only what is newer (see `ExtractionOptions::since`)
+pub struct LastRun {
+    path: PathBuf,
+}
+
+impl LastRun {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Reads the stored timestamp; a missing file means there was no previous run
+    pub fn load(&self) -> Result<Option<String>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let timestamp = contents.trim();
+        if timestamp_to_epoch(timestamp).is_none() {
+            return Err(format!("{} holds '{}', which is not a timestamp", self.path.display(), timestamp).into());
+        }
+        Ok(Some(timestamp.to_string()))
+    }
+
+    /// Replaces the stored timestamp, atomically so an interrupted save keeps the previous one
+    pub fn save(&self, timestamp: &str) -> Result<()> {
+        DirectorySink::new("").write_file(&self.path, format!("{}\n", timestamp).as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Comparing against the stored timestamp needs timestamp parsing
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_second_run_exports_only_newer_messages() {
+        use crate::application::{ExtractionOptions, ExtractionServiceImpl};
+        use crate::domain::ExtractedRecord;
+        use crate::ports::{DataRepository, MarkdownWriter};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct SharedRecords(Arc<Mutex<Vec<ExtractedRecord>>>);
+
+        impl DataRepository for SharedRecords {
+            fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+                Ok(self.0.lock().unwrap().clone())
+            }
+        }
+
+        impl MarkdownWriter for SharedRecords {
+            fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+                self.0.lock().unwrap().extend_from_slice(records);
+                Ok(())
+            }
+        }
+
+        fn record(timestamp: &str, content: &str) -> ExtractedRecord {
+            ExtractedRecord {
+                channel_name: "general".to_string(),
+                timestamp: timestamp.to_string(),
+                content: content.to_string(),
+                ..Default::default()
+            }
+        }
+
+        /// Runs an export limited by the state file, saving the new state like the CLI does
+        fn run_since_last_run(state: &LastRun, source: &SharedRecords) -> Vec<String> {
+            let written = SharedRecords::default();
+            let service = ExtractionServiceImpl::new(Box::new(source.clone()), Box::new(written.clone())).with_options(
+                ExtractionOptions {
+                    since: state.load().unwrap(),
+                    ..Default::default()
+                },
+            );
+            let report = service.execute_extraction().unwrap();
+            if let Some(latest) = report.latest_timestamp {
+                state.save(&latest).unwrap();
+            }
+            let contents = written.0.lock().unwrap().iter().map(|record| record.content.clone()).collect();
+            contents
+        }
+
+        let dir = TempDir::new().unwrap();
+        let state = LastRun::new(dir.path().join("last-run"));
+        let source = SharedRecords::default();
+        source.0.lock().unwrap().extend([
+            record("2025-12-15T09:00:00Z", "old"),
+            record("2025-12-16T10:30:00Z", "older"),
+        ]);
+
+        assert_eq!(run_since_last_run(&state, &source), vec!["old", "older"]);
+        assert_eq!(state.load().unwrap().as_deref(), Some("2025-12-16T10:30:00Z"));
+
+        source.0.lock().unwrap().extend([
+            record("2025-12-16T10:30:01Z", "new"),
+            record("2025-12-17T08:00:00Z", "newer"),
+        ]);
+        assert_eq!(run_since_last_run(&state, &source), vec!["new", "newer"]);
+        assert_eq!(state.load().unwrap().as_deref(), Some("2025-12-17T08:00:00Z"));
+
+        // Nothing new keeps the stored timestamp
+        assert!(run_since_last_run(&state, &source).is_empty());
+        assert_eq!(state.load().unwrap().as_deref(), Some("2025-12-17T08:00:00Z"));
+
+        // A message later within the same second as the stored one is still new
+        source.0.lock().unwrap().push(record("2025-12-18T12:00:00.250Z", "lunch"));
+        assert_eq!(run_since_last_run(&state, &source), vec!["lunch"]);
+        assert_eq!(state.load().unwrap().as_deref(), Some("2025-12-18T12:00:00.250Z"));
+        source.0.lock().unwrap().push(record("2025-12-18T12:00:00.750Z", "dessert"));
+        assert_eq!(run_since_last_run(&state, &source), vec!["dessert"]);
+    }
+
+    #[test]
+    fn test_load_rejects_garbage() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("last-run");
+        fs::write(&path, "yesterday-ish\n").unwrap();
+
+        assert!(LastRun::new(path).load().is_err());
+    }
+}
@@ -1,5 +1,13 @@
 pub mod application;
 pub mod domain;
+pub mod last_run;
+pub mod logging;
+pub mod merge;
 pub mod ports;
+pub mod registry;
+pub mod roster;
+pub mod sinks;
+pub mod summary;
+pub mod transforms;
 pub mod utils;
 
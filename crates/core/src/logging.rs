@@ -0,0 +1,178 @@
+use log::kv::{Error as KvError, Key, Value, VisitSource};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Number, Value as JsonValue};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Output format of log records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable `LEVEL message key=value` lines
+    #[default]
+    Text,
+    /// One JSON object per line with `level`, `message` and any structured fields
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!("unknown log format '{}' (expected text or json)", other)),
+        }
+    }
+}
+
+/// Collects the structured key-value fields of a record
+#[derive(Default)]
+struct Fields(Vec<(String, JsonValue)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> std::result::Result<(), KvError> {
+        self.0.push((key.to_string(), json_value(&value)));
+        Ok(())
+    }
+}
+
+/// Maps a log value to JSON, keeping numbers and booleans typed
+fn json_value(value: &Value) -> JsonValue {
+    if let Some(number) = value.to_u64() {
+        JsonValue::from(number)
+    } else if let Some(number) = value.to_i64() {
+        JsonValue::from(number)
+    } else if let Some(number) = value.to_f64().and_then(Number::from_f64) {
+        JsonValue::Number(number)
+    } else if let Some(flag) = value.to_bool() {
+        JsonValue::Bool(flag)
+    } else {
+        JsonValue::String(value.to_string())
+    }
+}
+
+/// Formats a log record as a single line (without the trailing newline)
+/// JSON escaping keeps multi-line messages on one line
+pub fn format_record(format: LogFormat, record: &Record) -> String {
+    let mut fields = Fields::default();
+    // Visiting an in-memory source only fails if the visitor does, which ours never does
+    let _ = record.key_values().visit(&mut fields);
+
+    match format {
+        LogFormat::Text => {
+            let mut line = format!("{} {}", record.level(), record.args());
+            for (key, value) in fields.0 {
+                match value {
+                    JsonValue::String(text) => line.push_str(&format!(" {}={}", key, text)),
+                    other => line.push_str(&format!(" {}={}", key, other)),
+                }
+            }
+            line
+        }
+        LogFormat::Json => {
+            let mut object = Map::new();
+            object.insert("level".to_string(), JsonValue::from(record.level().as_str()));
+            object.insert("target".to_string(), JsonValue::from(record.target()));
+            object.insert("message".to_string(), JsonValue::from(record.args().to_string()));
+            for (key, value) in fields.0 {
+                object.insert(key, value);
+            }
+            JsonValue::Object(object).to_string()
+        }
+    }
+}
+
+/// Logger writing one formatted line per record to stderr
+pub struct StderrLogger {
+    format: LogFormat,
+    level: LevelFilter,
+    stderr: Mutex<io::Stderr>,
+}
+
+impl StderrLogger {
+    /// Installs the logger as the global `log` backend
+    pub fn init(format: LogFormat, level: LevelFilter) -> std::result::Result<(), log::SetLoggerError> {
+        let logger = Self {
+            format,
+            level,
+            stderr: Mutex::new(io::stderr()),
+        };
+        log::set_boxed_logger(Box::new(logger))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut stderr) = self.stderr.lock() {
+            let _ = writeln!(stderr, "{}", format_record(self.format, record));
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut stderr) = self.stderr.lock() {
+            let _ = stderr.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn format_with_fields(format: LogFormat, message: &str) -> String {
+        let fields: [(&str, Value); 2] = [("channel", Value::from("general")), ("count", Value::from(42u64))];
+        format_record(
+            format,
+            &Record::builder()
+                .level(Level::Info)
+                .target("core::application")
+                .args(format_args!("{}", message))
+                .key_values(&fields)
+                .build(),
+        )
+    }
+
+    #[test]
+    fn test_json_log_lines_are_json_objects() {
+        let output = [
+            format_with_fields(LogFormat::Json, "wrote file"),
+            format_with_fields(LogFormat::Json, "multi\nline \"quoted\" message"),
+        ]
+        .join("\n");
+
+        for line in output.lines() {
+            let parsed: JsonValue = serde_json::from_str(line).unwrap();
+            let object = parsed.as_object().unwrap();
+            assert_eq!(object["level"], "INFO");
+            assert_eq!(object["channel"], "general");
+            assert_eq!(object["count"], 42);
+        }
+        assert_eq!(output.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_text_log_line() {
+        assert_eq!(
+            format_with_fields(LogFormat::Text, "wrote file"),
+            "INFO wrote file channel=general count=42"
+        );
+    }
+
+    #[test]
+    fn test_log_format_from_str() {
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::domain::ExtractedRecord;
+use crate::ports::{DataRepository, Result};
+use std::collections::HashMap;
+
+/// Combines several repositories into one, e.g. exports of the same server from several databases
+/// Records are merged in stored-timestamp order, the same ordering a single SQLite source uses
+pub struct MergingRepository {
+    repositories: Vec<Box<dyn DataRepository>>,
+}
+
+impl MergingRepository {
+    pub fn new(repositories: Vec<Box<dyn DataRepository>>) -> Self {
+        Self { repositories }
+    }
+}
+
+impl DataRepository for MergingRepository {
+    fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+        let mut records = Vec::new();
+        for repository in &self.repositories {
+            records.extend(repository.fetch_all_records()?);
+        }
+        // Stable, so records with equal timestamps keep their source order
+        records.sort_by(|a, b| a.raw_timestamp.cmp(&b.raw_timestamp));
+        Ok(records)
+    }
+
+    /// Later repositories win when the same user id maps to different names
+    fn fetch_usernames(&self) -> Result<HashMap<String, String>> {
+        let mut usernames = HashMap::new();
+        for repository in &self.repositories {
+            usernames.extend(repository.fetch_usernames()?);
+        }
+        Ok(usernames)
+    }
+
+    /// Later repositories win when the same channel id maps to different names
+    fn fetch_channel_names(&self) -> Result<HashMap<String, String>> {
+        let mut channel_names = HashMap::new();
+        for repository in &self.repositories {
+            channel_names.extend(repository.fetch_channel_names()?);
+        }
+        Ok(channel_names)
+    }
+}
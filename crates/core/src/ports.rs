@@ -1,11 +1,58 @@
 use crate::domain::ExtractedRecord;
+use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
+use std::path::Path;
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 pub trait DataRepository {
     // Fetches joined data and maps it to ExtractedRecord
     fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>>;
+
+    /// Hands every record to `f` in the same order as `fetch_all_records`, stopping at the first
+    /// error. The default loads every record first; sources that can read row by row override it.
+    /// The extraction service still buffers the whole export before writing, so only its
+    /// per-channel mode bounds memory; streaming keeps the default `fetch_channel_ids` and
+    /// `fetch_by_channel` below from holding every record
+    fn fetch_records_streaming(&self, f: &mut dyn FnMut(ExtractedRecord) -> Result<()>) -> Result<()> {
+        self.fetch_all_records()?.into_iter().try_for_each(f)
+    }
+
+    /// Returns known usernames keyed by user id, used to resolve mentions
+    /// Sources without a user directory return an empty map
+    fn fetch_usernames(&self) -> Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+
+    /// Returns known channel names keyed by channel id, used to resolve channel mentions
+    /// Sources without a channel directory return an empty map
+    fn fetch_channel_names(&self) -> Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+
+    /// Returns the ids of channels that have records, for fetching one channel at a time
+    /// The default reads every record; sources that can query by channel should override this
+    /// and `fetch_by_channel`. Records without a channel id are not reachable this way
+    fn fetch_channel_ids(&self) -> Result<Vec<String>> {
+        let mut ids = BTreeSet::new();
+        self.fetch_records_streaming(&mut |record| {
+            ids.extend(record.channel_id);
+            Ok(())
+        })?;
+        Ok(ids.into_iter().collect())
+    }
+
+    /// Fetches the records of a single channel, in the same order as `fetch_all_records`
+    fn fetch_by_channel(&self, channel_id: &str) -> Result<Vec<ExtractedRecord>> {
+        let mut records = Vec::new();
+        self.fetch_records_streaming(&mut |record| {
+            if record.channel_id.as_deref() == Some(channel_id) {
+                records.push(record);
+            }
+            Ok(())
+        })?;
+        Ok(records)
+    }
 }
 
 /// Trait for writing markdown content
@@ -14,3 +61,14 @@ pub trait MarkdownWriter: Send + Sync {
     fn write(&self, records: &[ExtractedRecord]) -> Result<()>;
 }
 
+/// Destination for the files produced by writers
+/// Paths are relative to the sink, so the same writer can target a directory or an archive
+pub trait OutputSink: Send + Sync {
+    fn write_file(&self, relative_path: &Path, contents: &[u8]) -> Result<()>;
+
+    /// Reads back a file written by an earlier run, or `None` if it does not exist
+    /// Sinks that cannot be read back (archives, streams, dry runs) never have earlier files
+    fn read_file(&self, _relative_path: &Path) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
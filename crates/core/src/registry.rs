@@ -0,0 +1,158 @@
+use crate::ports::{DataRepository, MarkdownWriter, OutputSink, Result};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Builds a repository reading from the given source, e.g. a database path
+pub type RepositoryFactory = Box<dyn Fn(&str) -> Result<Box<dyn DataRepository>>>;
+
+/// Builds a writer emitting its files into the given sink
+pub type WriterFactory = Box<dyn Fn(Arc<dyn OutputSink>) -> Result<Box<dyn MarkdownWriter>>>;
+
+/// Named repository and writer factories, so sources and output formats can be selected by name
+/// The CLI registers the built-in adapters; custom schemas and formats register alongside them
+#[derive(Default)]
+pub struct Registry {
+    repositories: BTreeMap<String, RepositoryFactory>,
+    writers: BTreeMap<String, WriterFactory>,
+}
+
+impl Registry {
+    /// Registers a repository factory, replacing any previously registered under `name`
+    pub fn register_repository(
+        &mut self,
+        name: &str,
+        factory: impl Fn(&str) -> Result<Box<dyn DataRepository>> + 'static,
+    ) {
+        self.repositories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// Registers a writer factory, replacing any previously registered under `name`
+    pub fn register_writer(
+        &mut self,
+        name: &str,
+        factory: impl Fn(Arc<dyn OutputSink>) -> Result<Box<dyn MarkdownWriter>> + 'static,
+    ) {
+        self.writers.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// Builds the repository registered under `name` for the given source
+    pub fn repository(&self, name: &str, source: &str) -> Result<Box<dyn DataRepository>> {
+        match self.repositories.get(name) {
+            Some(factory) => factory(source),
+            None => Err(unknown("input format", name, self.repositories.keys()).into()),
+        }
+    }
+
+    /// Builds the writer registered under `name` for the given sink
+    pub fn writer(&self, name: &str, sink: Arc<dyn OutputSink>) -> Result<Box<dyn MarkdownWriter>> {
+        match self.writers.get(name) {
+            Some(factory) => factory(sink),
+            None => Err(unknown("output format", name, self.writers.keys()).into()),
+        }
+    }
+}
+
+/// Describes an unregistered name along with the registered alternatives
+fn unknown<'a>(kind: &str, name: &str, available: impl Iterator<Item = &'a String>) -> String {
+    let available: Vec<&str> = available.map(String::as_str).collect();
+    format!("unknown {} '{}' (available: {})", kind, name, available.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::ExtractionServiceImpl;
+    use crate::domain::ExtractedRecord;
+    use crate::sinks::DirectorySink;
+    use std::sync::Mutex;
+
+    struct FakeRepository {
+        source: String,
+    }
+
+    impl DataRepository for FakeRepository {
+        fn fetch_all_records(&self) -> Result<Vec<ExtractedRecord>> {
+            Ok(vec![ExtractedRecord {
+                channel_name: "general".to_string(),
+                content: format!("from {}", self.source),
+                ..Default::default()
+            }])
+        }
+    }
+
+    struct CapturingWriter {
+        written: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MarkdownWriter for CapturingWriter {
+        fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+            let mut written = self.written.lock().unwrap();
+            written.extend(records.iter().map(|record| record.content.clone()));
+            Ok(())
+        }
+    }
+
+    /// Writes one fixed file into its sink
+    struct FileWriter {
+        sink: Arc<dyn OutputSink>,
+        name: &'static str,
+    }
+
+    impl MarkdownWriter for FileWriter {
+        fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+            self.sink.write_file(std::path::Path::new(self.name), records[0].content.as_bytes())
+        }
+    }
+
+    #[test]
+    fn test_pipeline_runs_through_registered_names() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = Registry::default();
+        registry.register_repository("fake", |source| {
+            Ok(Box::new(FakeRepository {
+                source: source.to_string(),
+            }))
+        });
+        let captured = written.clone();
+        registry.register_writer("capture", move |_sink| {
+            Ok(Box::new(CapturingWriter {
+                written: captured.clone(),
+            }))
+        });
+
+        let sink: Arc<dyn OutputSink> = Arc::new(DirectorySink::new(""));
+        let service = ExtractionServiceImpl::new(
+            registry.repository("fake", "custom.db").unwrap(),
+            registry.writer("capture", sink.clone()).unwrap(),
+        );
+        service.execute_extraction().unwrap();
+
+        assert_eq!(*written.lock().unwrap(), vec!["from custom.db"]);
+        let error = registry.writer("html", sink).err().unwrap().to_string();
+        assert_eq!(error, "unknown output format 'html' (available: capture)");
+    }
+
+    #[test]
+    fn test_each_format_writes_to_its_own_sink() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut registry = Registry::default();
+        registry.register_writer("markdown", |sink| Ok(Box::new(FileWriter { sink, name: "general.md" })));
+        registry.register_writer("json", |sink| Ok(Box::new(FileWriter { sink, name: "records.json" })));
+        let markdown_sink: Arc<dyn OutputSink> = Arc::new(DirectorySink::new(dir.path().join("notes")));
+        let json_sink: Arc<dyn OutputSink> = Arc::new(DirectorySink::new(dir.path().join("data")));
+
+        let service = ExtractionServiceImpl::new(
+            Box::new(FakeRepository {
+                source: "archive.db".to_string(),
+            }),
+            registry.writer("markdown", markdown_sink).unwrap(),
+        )
+        .with_writer(registry.writer("json", json_sink).unwrap());
+        service.execute_extraction().unwrap();
+
+        assert!(dir.path().join("notes/general.md").is_file());
+        assert!(dir.path().join("data/records.json").is_file());
+        assert!(!dir.path().join("notes/records.json").exists());
+        assert!(!dir.path().join("data/general.md").exists());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::domain::ExtractedRecord;
+use std::collections::BTreeSet;
+
+/// A unique author encountered in the extracted records
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RosterEntry {
+    pub username: String,
+    pub user_id: Option<String>,
+}
+
+/// Collects the unique authors of the given records
+/// Authors are identified by (username, user_id) and sorted case-insensitively by username
+pub fn build_roster(records: &[ExtractedRecord]) -> Vec<RosterEntry> {
+    let unique: BTreeSet<RosterEntry> = records
+        .iter()
+        .map(|record| RosterEntry {
+            username: record.username.clone(),
+            user_id: record.user_id.clone(),
+        })
+        .collect();
+
+    let mut roster: Vec<RosterEntry> = unique.into_iter().collect();
+    roster.sort_by_cached_key(|entry| (entry.username.to_lowercase(), entry.clone()));
+    roster
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(username: &str, user_id: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            username: username.to_string(),
+            user_id: Some(user_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_roster_unique_and_sorted() {
+        let records = vec![
+            record("carol", "u3"),
+            record("alice", "u1"),
+            record("Bob", "u2"),
+            record("alice", "u1"),
+            record("carol", "u3"),
+        ];
+
+        let roster = build_roster(&records);
+
+        let names: Vec<&str> = roster.iter().map(|e| e.username.as_str()).collect();
+        assert_eq!(names, vec!["alice", "Bob", "carol"]);
+        assert_eq!(roster[0].user_id.as_deref(), Some("u1"));
+    }
+
+    #[test]
+    fn test_build_roster_empty() {
+        assert!(build_roster(&[]).is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+use crate::ports::{OutputSink, Result};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Writes files below a root directory on the local filesystem
+/// Parent directories are created as needed. Each file is written to a temporary
+/// sibling and renamed into place, so readers never observe a partial file
+pub struct DirectorySink {
+    root: PathBuf,
+    resume: bool,
+    fsync: bool,
+}
+
+impl DirectorySink {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            resume: false,
+            fsync: false,
+        }
+    }
+
+    /// Leaves existing non-empty files untouched when their contents would not change,
+    /// so re-running an interrupted export only writes what is missing or different
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Flushes each file to disk before renaming it into place, and the directory after, so a
+    /// written file survives a crash or power loss; slower, so off by default
+    pub fn with_fsync(mut self, fsync: bool) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
+    /// Writes `contents` to `path`, flushed to disk first when `fsync` is set
+    fn write_temp(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        if !self.fsync {
+            return fs::write(path, contents);
+        }
+        let mut file = fs::File::create(path)?;
+        file.write_all(contents)?;
+        file.sync_all()
+    }
+}
+
+impl OutputSink for DirectorySink {
+    fn write_file(&self, relative_path: &Path, contents: &[u8]) -> Result<()> {
+        let file_path = self.root.join(relative_path);
+        if self.resume && !contents.is_empty() && fs::read(&file_path).is_ok_and(|existing| existing == contents) {
+            log::info!(file = file_path.to_string_lossy().as_ref(); "skipping unchanged file");
+            return Ok(());
+        }
+        if let Some(parent) = file_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file_name = file_path
+            .file_name()
+            .ok_or_else(|| format!("output path has no file name: {}", file_path.display()))?;
+        let mut temp_name = OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = file_path.with_file_name(temp_name);
+
+        if let Err(e) = self.write_temp(&temp_path, contents).and_then(|_| fs::rename(&temp_path, &file_path)) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        // The rename is only durable once the directory entry itself is flushed
+        #[cfg(unix)]
+        if self.fsync {
+            let parent = file_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            fs::File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn read_file(&self, relative_path: &Path) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.root.join(relative_path)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Places every file below a fixed subdirectory of another sink
+pub struct PrefixedSink {
+    inner: Arc<dyn OutputSink>,
+    prefix: PathBuf,
+}
+
+impl PrefixedSink {
+    pub fn new(inner: Arc<dyn OutputSink>, prefix: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl OutputSink for PrefixedSink {
+    fn write_file(&self, relative_path: &Path, contents: &[u8]) -> Result<()> {
+        self.inner.write_file(&self.prefix.join(relative_path), contents)
+    }
+
+    fn read_file(&self, relative_path: &Path) -> Result<Option<Vec<u8>>> {
+        self.inner.read_file(&self.prefix.join(relative_path))
+    }
+}
+
+/// Records the size of every file instead of writing it, for dry runs
+/// Writing the same path twice keeps the latest size, as a real sink would
+#[derive(Default)]
+pub struct MeasuringSink {
+    sizes: Mutex<BTreeMap<PathBuf, usize>>,
+}
+
+impl MeasuringSink {
+    /// Returns each file with its size in bytes, sorted by path
+    pub fn sizes(&self) -> Vec<(PathBuf, usize)> {
+        match self.sizes.lock() {
+            Ok(sizes) => sizes.iter().map(|(path, size)| (path.clone(), *size)).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Total size of all files in bytes
+    pub fn total(&self) -> usize {
+        self.sizes().iter().map(|(_, size)| size).sum()
+    }
+}
+
+impl OutputSink for MeasuringSink {
+    fn write_file(&self, relative_path: &Path, contents: &[u8]) -> Result<()> {
+        let mut sizes = self.sizes.lock().map_err(|_| "size table lock poisoned")?;
+        sizes.insert(relative_path.to_path_buf(), contents.len());
+        Ok(())
+    }
+}
+
+/// Writes every file, one after another, to a single stream such as stdout
+/// With separators enabled each file is preceded by a `==> path <==` line, so
+/// inherently multi-file outputs (e.g. per-channel Markdown) stay readable
+pub struct StreamSink<W: Write + Send> {
+    stream: Mutex<(W, usize)>,
+    separators: bool,
+}
+
+impl<W: Write + Send> StreamSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            stream: Mutex::new((writer, 0)),
+            separators: false,
+        }
+    }
+
+    /// Precedes each file with a header line naming it
+    pub fn with_separators(mut self, separators: bool) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> Result<W> {
+        let (writer, _) = self.stream.into_inner().map_err(|_| "output stream lock poisoned")?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write + Send> OutputSink for StreamSink<W> {
+    fn write_file(&self, relative_path: &Path, contents: &[u8]) -> Result<()> {
+        let mut guard = self.stream.lock().map_err(|_| "output stream lock poisoned")?;
+        let (writer, files_written) = &mut *guard;
+        if self.separators {
+            if *files_written > 0 {
+                writeln!(writer)?;
+            }
+            writeln!(writer, "==> {} <==\n", relative_path.display())?;
+        }
+        writer.write_all(contents)?;
+        writer.flush()?;
+        *files_written += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_directory_sink_leaves_no_temp_files() {
+        let dir = TempDir::new().unwrap();
+        let sink = DirectorySink::new(dir.path());
+
+        sink.write_file(Path::new("general-2025-12.md"), b"first").unwrap();
+        sink.write_file(Path::new("nested/random-2025-12.md"), b"nested").unwrap();
+        sink.write_file(Path::new("general-2025-12.md"), b"replaced").unwrap();
+
+        assert_eq!(fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap(), "replaced");
+        assert_eq!(fs::read_to_string(dir.path().join("nested/random-2025-12.md")).unwrap(), "nested");
+        for entry in fs::read_dir(dir.path()).unwrap().chain(fs::read_dir(dir.path().join("nested")).unwrap()) {
+            let name = entry.unwrap().file_name().to_string_lossy().into_owned();
+            assert!(!name.ends_with(".tmp"), "leftover temp file {}", name);
+        }
+    }
+
+    #[test]
+    fn test_fsync_writes_complete_files_without_leftovers() {
+        let dir = TempDir::new().unwrap();
+        let sink = DirectorySink::new(dir.path()).with_fsync(true);
+
+        sink.write_file(Path::new("general-2025-12.md"), b"first").unwrap();
+        sink.write_file(Path::new("general-2025-12.md"), b"second").unwrap();
+        sink.write_file(Path::new("nested/random-2025-12.md"), b"nested").unwrap();
+
+        assert_eq!(fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap(), "second");
+        assert_eq!(fs::read_to_string(dir.path().join("nested/random-2025-12.md")).unwrap(), "nested");
+        let names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert!(names.iter().all(|name| !name.ends_with(".tmp")), "{:?}", names);
+    }
+
+    #[test]
+    fn test_resume_skips_unchanged_files() {
+        let dir = TempDir::new().unwrap();
+        let existing = dir.path().join("general-2025-11.md");
+        fs::write(&existing, "november").unwrap();
+        let epoch = std::time::SystemTime::UNIX_EPOCH;
+        fs::File::options().write(true).open(&existing).unwrap().set_modified(epoch).unwrap();
+        let sink = DirectorySink::new(dir.path()).with_resume(true);
+
+        sink.write_file(Path::new("general-2025-11.md"), b"november").unwrap();
+        sink.write_file(Path::new("general-2025-12.md"), b"december").unwrap();
+
+        assert_eq!(fs::metadata(&existing).unwrap().modified().unwrap(), epoch);
+        assert_eq!(fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap(), "december");
+    }
+
+    #[test]
+    fn test_resume_rewrites_changed_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("general-2025-12.md"), "stale").unwrap();
+        let sink = DirectorySink::new(dir.path()).with_resume(true);
+
+        sink.write_file(Path::new("general-2025-12.md"), b"december").unwrap();
+
+        assert_eq!(fs::read_to_string(dir.path().join("general-2025-12.md")).unwrap(), "december");
+    }
+
+    #[test]
+    fn test_read_file_returns_earlier_contents() {
+        let dir = TempDir::new().unwrap();
+        let sink = PrefixedSink::new(Arc::new(DirectorySink::new(dir.path())), "month");
+
+        assert_eq!(sink.read_file(Path::new("general-2025-12.md")).unwrap(), None);
+        sink.write_file(Path::new("general-2025-12.md"), b"december").unwrap();
+        assert_eq!(sink.read_file(Path::new("general-2025-12.md")).unwrap(), Some(b"december".to_vec()));
+    }
+
+    #[test]
+    fn test_stream_sink_separates_files() {
+        let sink = StreamSink::new(Vec::new()).with_separators(true);
+
+        sink.write_file(Path::new("general-2025-12.md"), b"# general\n").unwrap();
+        sink.write_file(Path::new("random-2025-12.md"), b"# random\n").unwrap();
+
+        let output = String::from_utf8(sink.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "==> general-2025-12.md <==\n\n# general\n\n==> random-2025-12.md <==\n\n# random\n"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::domain::ExtractedRecord;
+use crate::utils::{content_hash, count_emoji, count_links, extract_year_month_in, timestamp_to_epoch, OutputTimezone};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Aggregate activity statistics over a set of extracted records
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub total_messages: usize,
+    pub channels: BTreeMap<String, usize>,
+    pub authors: BTreeMap<String, usize>,
+    pub months: BTreeMap<String, usize>,
+    pub date_range: Option<DateRange>,
+    /// First and last message of each channel with a parseable timestamp
+    pub channel_ranges: BTreeMap<String, ChannelRange>,
+    /// Fraction of messages containing at least one emoji (0 without messages)
+    pub emoji_message_ratio: f64,
+    /// Mean number of emoji per message (0 without messages)
+    pub emoji_per_message: f64,
+    /// Total number of links across all messages
+    pub link_count: usize,
+    /// Messages flagged as duplicates (0 unless duplicates were flagged)
+    pub duplicate_messages: usize,
+    /// Groups of flagged messages sharing the same normalized content
+    pub duplicate_clusters: usize,
+    /// When the summary was generated, if the writer stamps it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_at: Option<String>,
+}
+
+/// Timestamps of the first and last dated records
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DateRange {
+    pub first: String,
+    pub last: String,
+}
+
+/// Earliest and latest message timestamps of a channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelRange {
+    pub first: String,
+    pub last: String,
+    /// Whole days between the first and last message
+    pub span_days: i64,
+}
+
+/// A timestamp as parsed epoch seconds and as written
+type SeenAt = (i64, String);
+
+/// Collects per-channel first and last timestamps, comparing parsed times so mixed formats
+/// order correctly; timestamps that don't parse are ignored
+#[derive(Debug, Default)]
+pub struct ChannelRanges {
+    ranges: BTreeMap<String, (SeenAt, SeenAt)>,
+}
+
+impl ChannelRanges {
+    pub fn add(&mut self, channel: &str, timestamp: &str) {
+        let Some(epoch) = timestamp_to_epoch(timestamp) else {
+            return;
+        };
+        let seen = (epoch, timestamp.to_string());
+        match self.ranges.get_mut(channel) {
+            Some((first, last)) => {
+                if epoch < first.0 {
+                    *first = seen;
+                } else if epoch > last.0 {
+                    *last = seen;
+                }
+            }
+            None => {
+                self.ranges.insert(channel.to_string(), (seen.clone(), seen));
+            }
+        }
+    }
+
+    pub fn finish(self) -> BTreeMap<String, ChannelRange> {
+        self.ranges
+            .into_iter()
+            .map(|(channel, (first, last))| {
+                let range = ChannelRange {
+                    span_days: (last.0 - first.0) / 86_400,
+                    first: first.1,
+                    last: last.1,
+                };
+                (channel, range)
+            })
+            .collect()
+    }
+}
+
+/// Formats channel ranges as an aligned plain-text table, one channel per row
+pub fn format_channel_ranges(ranges: &BTreeMap<String, ChannelRange>) -> String {
+    let rows: Vec<[String; 4]> = ranges
+        .iter()
+        .map(|(channel, range)| {
+            [format!("#{}", channel), range.first.clone(), range.last.clone(), range.span_days.to_string()]
+        })
+        .collect();
+    let header = ["channel", "first", "last", "days"].map(String::from);
+    let mut widths = [0; 4];
+    for row in rows.iter().chain([&header]) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::new();
+    for row in [&header].into_iter().chain(&rows) {
+        let line = format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {:>w3$}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Computes message counts per channel, author and month, plus the covered date range
+/// Records are expected in timestamp order, as returned by the repository;
+/// records without a timestamp are counted but don't affect the date range
+pub fn build_summary(records: &[ExtractedRecord]) -> Summary {
+    build_summary_in(records, OutputTimezone::Local)
+}
+
+/// Like `build_summary`, with months taken in the given timezone
+pub fn build_summary_in(records: &[ExtractedRecord], timezone: OutputTimezone) -> Summary {
+    let mut summary = Summary {
+        total_messages: records.len(),
+        ..Default::default()
+    };
+
+    let mut clusters: HashMap<u64, usize> = HashMap::new();
+    let mut channel_ranges = ChannelRanges::default();
+    let mut messages_with_emoji = 0;
+    let mut emoji = 0;
+    for record in records {
+        let record_emoji = count_emoji(&record.content);
+        emoji += record_emoji;
+        messages_with_emoji += usize::from(record_emoji > 0);
+        summary.link_count += count_links(&record.content);
+        if record.duplicate {
+            *clusters.entry(content_hash(&record.content)).or_default() += 1;
+        }
+        *summary.channels.entry(record.channel_name.clone()).or_default() += 1;
+        channel_ranges.add(&record.channel_name, &record.timestamp);
+        *summary.authors.entry(record.username.clone()).or_default() += 1;
+
+        let year_month = extract_year_month_in(&record.timestamp, timezone).unwrap_or_else(|| "unknown".to_string());
+        *summary.months.entry(year_month).or_default() += 1;
+    }
+
+    summary.channel_ranges = channel_ranges.finish();
+    summary.duplicate_messages = clusters.values().sum();
+    summary.duplicate_clusters = clusters.len();
+
+    if !records.is_empty() {
+        summary.emoji_message_ratio = messages_with_emoji as f64 / records.len() as f64;
+        summary.emoji_per_message = emoji as f64 / records.len() as f64;
+    }
+
+    let mut dated = records.iter().filter(|record| !record.timestamp.is_empty());
+    if let Some(first) = dated.next() {
+        let last = dated.next_back().unwrap_or(first);
+        summary.date_range = Some(DateRange {
+            first: first.timestamp.clone(),
+            last: last.timestamp.clone(),
+        });
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(channel: &str, username: &str, timestamp: &str) -> ExtractedRecord {
+        ExtractedRecord {
+            channel_name: channel.to_string(),
+            username: username.to_string(),
+            timestamp: timestamp.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_summary_counts() {
+        let records = vec![
+            record("general", "alice", "2025-11-30 12:00:00"),
+            record("general", "bob", "2025-12-01 12:00:00"),
+            record("random", "alice", "2025-12-02 12:00:00"),
+            record("random", "alice", ""),
+        ];
+
+        let summary = build_summary(&records);
+
+        assert_eq!(summary.total_messages, 4);
+        assert_eq!(summary.channels["general"], 2);
+        assert_eq!(summary.channels["random"], 2);
+        assert_eq!(summary.authors["alice"], 3);
+        assert_eq!(summary.authors["bob"], 1);
+        assert_eq!(summary.months["2025-11"], 1);
+        assert_eq!(summary.months["2025-12"], 2);
+        assert_eq!(summary.months["unknown"], 1);
+        assert_eq!(
+            summary.date_range,
+            Some(DateRange {
+                first: "2025-11-30 12:00:00".to_string(),
+                last: "2025-12-02 12:00:00".to_string(),
+            })
+        );
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_build_summary_channel_ranges() {
+        let records = vec![
+            record("general", "alice", "2025-11-30 12:00:00"),
+            record("random", "bob", "2025-12-05 08:00:00"),
+            record("general", "bob", "2025-12-10 18:00:00"),
+            record("general", "alice", "2025-11-20 09:00:00"),
+            record("random", "alice", "not a time"),
+        ];
+
+        let summary = build_summary(&records);
+
+        let range = |first: &str, last: &str, span_days| ChannelRange {
+            first: first.to_string(),
+            last: last.to_string(),
+            span_days,
+        };
+        assert_eq!(summary.channel_ranges["general"], range("2025-11-20 09:00:00", "2025-12-10 18:00:00", 20));
+        assert_eq!(summary.channel_ranges["random"], range("2025-12-05 08:00:00", "2025-12-05 08:00:00", 0));
+
+        let table = format_channel_ranges(&summary.channel_ranges);
+        assert_eq!(
+            table.lines().collect::<Vec<_>>(),
+            vec![
+                "channel   first                last                 days",
+                "#general  2025-11-20 09:00:00  2025-12-10 18:00:00    20",
+                "#random   2025-12-05 08:00:00  2025-12-05 08:00:00     0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_summary_content_stats() {
+        let with_content = |content: &str| ExtractedRecord {
+            content: content.to_string(),
+            ..record("general", "alice", "2025-12-01 12:00:00")
+        };
+        let records = vec![
+            with_content("🎉🎉 see https://example.com"),
+            with_content("plain text"),
+            with_content("ok 👍 <https://a.io> https://b.io"),
+            with_content(""),
+        ];
+
+        let summary = build_summary(&records);
+
+        assert_eq!(summary.emoji_message_ratio, 0.5);
+        assert_eq!(summary.emoji_per_message, 0.75);
+        assert_eq!(summary.link_count, 3);
+    }
+
+    #[test]
+    fn test_build_summary_empty() {
+        let summary = build_summary(&[]);
+        assert_eq!(summary, Summary::default());
+    }
+}
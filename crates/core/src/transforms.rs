@@ -0,0 +1,468 @@
+use crate::domain::{ExtractedRecord, ExtractionReport};
+use crate::ports::{MarkdownWriter, Result};
+use crate::utils::strip_control_chars;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// A rewrite applied to every record before it reaches the writers
+/// Transforms are composable: the service applies them in registration order
+pub trait RecordTransform: Send + Sync {
+    fn apply(&self, record: &mut ExtractedRecord);
+
+    /// Adds anything the transform observed while applying to the run's report
+    fn report(&self, _report: &mut ExtractionReport) {}
+}
+
+/// Removes Discord mention tokens from message content for privacy
+/// Covers users (`<@id>`), nicknames (`<@!id>`), channels (`<#id>`) and roles (`<@&id>`)
+pub struct StripMentions {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl StripMentions {
+    /// Creates a transform replacing each mention with `replacement` (may be empty)
+    pub fn new(replacement: String) -> Self {
+        Self {
+            pattern: Regex::new(r"<(?:@[!&]?|#)\d+>").expect("mention pattern is valid"),
+            replacement,
+        }
+    }
+}
+
+impl RecordTransform for StripMentions {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        record.content = self
+            .pattern
+            .replace_all(&record.content, regex::NoExpand(&self.replacement))
+            .into_owned();
+    }
+}
+
+/// Removes raw control characters from message content (see `strip_control_chars`)
+pub struct SanitizeContent;
+
+impl RecordTransform for SanitizeContent {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        record.content = strip_control_chars(&record.content);
+    }
+}
+
+/// Built-in redaction pattern for email addresses
+pub const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+/// Built-in redaction pattern for phone numbers with at least 9 digits, e.g. `+1 (555) 123-4567`
+pub const PHONE_PATTERN: &str = r"\+?\d(?:[\s().-]*\d){8,}";
+/// Built-in redaction pattern for Discord tokens (three dot-separated base64url segments)
+pub const TOKEN_PATTERN: &str = r"[A-Za-z0-9_-]{23,28}\.[A-Za-z0-9_-]{6,7}\.[A-Za-z0-9_-]{27,}";
+
+/// Replaces every match of a set of patterns in message content with a fixed marker
+/// Patterns are compiled once, when the transform is created
+pub struct Redact {
+    patterns: Vec<Regex>,
+    replacement: String,
+}
+
+impl Redact {
+    /// Compiles the patterns, failing on the first invalid one
+    pub fn new(patterns: &[String], replacement: String) -> std::result::Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Self { patterns, replacement })
+    }
+}
+
+impl RecordTransform for Redact {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        for pattern in &self.patterns {
+            record.content = pattern
+                .replace_all(&record.content, regex::NoExpand(&self.replacement))
+                .into_owned();
+        }
+    }
+}
+
+/// Removes triple-backtick fences from message content, keeping the code itself
+/// Meant for plain formats where fences are clutter; the language tag, if kept,
+/// becomes a `[lang]` line in front of the code
+pub struct FlattenCodeBlocks {
+    pattern: Regex,
+    keep_language: bool,
+}
+
+impl FlattenCodeBlocks {
+    /// Creates a transform flattening fenced blocks, optionally keeping the language tag
+    pub fn new(keep_language: bool) -> Self {
+        Self {
+            pattern: Regex::new(r"(?s)```(?:([\w+#.-]+)?\n)?(.*?)```").expect("code fence pattern is valid"),
+            keep_language,
+        }
+    }
+}
+
+impl RecordTransform for FlattenCodeBlocks {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        record.content = self
+            .pattern
+            .replace_all(&record.content, |caps: &regex::Captures| {
+                let code = caps[2].trim_end_matches('\n');
+                match caps.get(1) {
+                    Some(language) if self.keep_language => format!("[{}]\n{}", language.as_str(), code),
+                    _ => code.to_string(),
+                }
+            })
+            .into_owned();
+    }
+}
+
+/// Applies transforms to the records seen by a single writer only
+/// Lets format-specific rewrites (e.g. `FlattenCodeBlocks` for plain outputs) leave other formats untouched
+pub struct TransformedWriter {
+    inner: Box<dyn MarkdownWriter>,
+    transforms: Vec<Box<dyn RecordTransform>>,
+}
+
+impl TransformedWriter {
+    pub fn new(inner: Box<dyn MarkdownWriter>, transforms: Vec<Box<dyn RecordTransform>>) -> Self {
+        Self { inner, transforms }
+    }
+}
+
+impl MarkdownWriter for TransformedWriter {
+    fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+        let mut records = records.to_vec();
+        for record in &mut records {
+            for transform in &self.transforms {
+                transform.apply(record);
+            }
+        }
+        self.inner.write(&records)
+    }
+}
+
+/// Replaces user mentions (`<@id>`, `<@!id>`) with `@username`
+/// Mentions of ids missing from the user map are left intact and recorded as unresolved
+pub struct ResolveMentions {
+    pattern: Regex,
+    usernames: Arc<HashMap<String, String>>,
+    unresolved: Mutex<BTreeSet<String>>,
+}
+
+impl ResolveMentions {
+    /// Creates a transform resolving mentions against usernames keyed by user id
+    /// The map can be shared (`Arc`) with other transforms and the repository that built it
+    pub fn new(usernames: impl Into<Arc<HashMap<String, String>>>) -> Self {
+        Self {
+            pattern: Regex::new(r"<@!?(\d+)>").expect("user mention pattern is valid"),
+            usernames: usernames.into(),
+            unresolved: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Returns the user ids seen in mentions that had no matching username
+    pub fn unresolved(&self) -> BTreeSet<String> {
+        self.unresolved.lock().map(|ids| ids.clone()).unwrap_or_default()
+    }
+}
+
+impl RecordTransform for ResolveMentions {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        let mut unresolved = Vec::new();
+        record.content = self
+            .pattern
+            .replace_all(&record.content, |caps: &regex::Captures| {
+                let id = &caps[1];
+                match self.usernames.get(id) {
+                    Some(username) => format!("@{}", username),
+                    None => {
+                        unresolved.push(id.to_string());
+                        caps[0].to_string()
+                    }
+                }
+            })
+            .into_owned();
+
+        if !unresolved.is_empty() {
+            if let Ok(mut ids) = self.unresolved.lock() {
+                ids.extend(unresolved);
+            }
+        }
+    }
+
+    fn report(&self, report: &mut ExtractionReport) {
+        report.unresolved_mentions.extend(self.unresolved());
+    }
+}
+
+/// Replaces channel mentions (`<#id>`) with `#name`; unknown channels are left intact
+pub struct ResolveChannelMentions {
+    pattern: Regex,
+    channel_names: Arc<HashMap<String, String>>,
+    format: fn(&str) -> String,
+}
+
+impl ResolveChannelMentions {
+    /// Creates a transform resolving channel mentions against channel names keyed by id
+    pub fn new(channel_names: impl Into<Arc<HashMap<String, String>>>) -> Self {
+        Self {
+            pattern: Regex::new(r"<#(\d+)>").expect("channel mention pattern is valid"),
+            channel_names: channel_names.into(),
+            format: |name| format!("#{}", name),
+        }
+    }
+
+    /// Renders resolved mentions with `format` instead of as `#name`, e.g. as an Obsidian tag
+    pub fn with_format(mut self, format: fn(&str) -> String) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl RecordTransform for ResolveChannelMentions {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        record.content = self
+            .pattern
+            .replace_all(&record.content, |caps: &regex::Captures| match self.channel_names.get(&caps[1]) {
+                Some(name) => (self.format)(name),
+                None => caps[0].to_string(),
+            })
+            .into_owned();
+    }
+}
+
+/// Attributes messages from several user ids to one canonical identity, e.g. a person who
+/// appears under an old discriminator name and a new handle
+/// Each mapped record takes the identity as its username and the identity's lowest mapped id
+/// as its user id, so per-author outputs merge them; unmapped users pass through
+pub struct MergeIdentities {
+    identities: HashMap<String, (String, String)>,
+}
+
+impl MergeIdentities {
+    /// Creates a transform from identity names keyed by user id
+    pub fn new(identities: HashMap<String, String>) -> Self {
+        let mut canonical_ids: HashMap<&str, &str> = HashMap::new();
+        for (user_id, identity) in &identities {
+            let canonical = canonical_ids.entry(identity).or_insert(user_id);
+            if user_id.as_str() < *canonical {
+                *canonical = user_id;
+            }
+        }
+        let identities = identities
+            .iter()
+            .map(|(user_id, identity)| {
+                let canonical_id = canonical_ids[identity.as_str()].to_string();
+                (user_id.clone(), (identity.clone(), canonical_id))
+            })
+            .collect();
+        Self { identities }
+    }
+
+    /// Parses an identity map given as a JSON object of user id to identity name
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+}
+
+impl RecordTransform for MergeIdentities {
+    fn apply(&self, record: &mut ExtractedRecord) {
+        let Some((identity, canonical_id)) = record.user_id.as_ref().and_then(|id| self.identities.get(id)) else {
+            return;
+        };
+        record.username = identity.clone();
+        record.user_id = Some(canonical_id.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn strip(content: &str, replacement: &str) -> String {
+        let mut record = ExtractedRecord {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        StripMentions::new(replacement.to_string()).apply(&mut record);
+        record.content
+    }
+
+    #[test]
+    fn test_strip_mentions_user() {
+        assert_eq!(strip("hi <@123456> there", "[mention]"), "hi [mention] there");
+    }
+
+    #[test]
+    fn test_strip_mentions_nickname() {
+        assert_eq!(strip("hi <@!123456>", "[mention]"), "hi [mention]");
+    }
+
+    #[test]
+    fn test_strip_mentions_channel() {
+        assert_eq!(strip("see <#987654>", "[mention]"), "see [mention]");
+    }
+
+    #[test]
+    fn test_strip_mentions_role() {
+        assert_eq!(strip("ping <@&555>!", "[mention]"), "ping [mention]!");
+    }
+
+    #[test]
+    fn test_strip_mentions_to_nothing() {
+        assert_eq!(strip("<@1><#2><@&3> done", ""), " done");
+    }
+
+    #[test]
+    fn test_strip_mentions_leaves_other_content() {
+        assert_eq!(strip("email <me@example.com> and <@name>", ""), "email <me@example.com> and <@name>");
+    }
+
+    fn resolver() -> ResolveMentions {
+        let usernames = HashMap::from([("123".to_string(), "alice".to_string())]);
+        ResolveMentions::new(usernames)
+    }
+
+    fn resolve(transform: &ResolveMentions, content: &str) -> String {
+        let mut record = ExtractedRecord {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        transform.apply(&mut record);
+        record.content
+    }
+
+    #[test]
+    fn test_resolve_mentions_known_user() {
+        let transform = resolver();
+        assert_eq!(resolve(&transform, "hi <@123> and <@!123>"), "hi @alice and @alice");
+        assert!(transform.unresolved().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_mentions_collects_unresolved_ids() {
+        let transform = resolver();
+        assert_eq!(resolve(&transform, "hi <@999> and <#5>"), "hi <@999> and <#5>");
+        resolve(&transform, "again <@!999>, and <@777>");
+
+        let mut report = ExtractionReport::default();
+        transform.report(&mut report);
+        let ids: Vec<&str> = report.unresolved_mentions.iter().map(String::as_str).collect();
+        assert_eq!(ids, vec!["777", "999"]);
+    }
+
+    fn redact(patterns: &[&str], content: &str) -> String {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        let mut record = ExtractedRecord {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        Redact::new(&patterns, "[REDACTED]".to_string()).unwrap().apply(&mut record);
+        record.content
+    }
+
+    #[test]
+    fn test_redact_email_preset() {
+        assert_eq!(
+            redact(&[EMAIL_PATTERN], "mail jane.doe+work@example.co.uk or bob@test.io"),
+            "mail [REDACTED] or [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_redact_phone_and_token_presets() {
+        assert_eq!(redact(&[PHONE_PATTERN], "call +1 (555) 123-4567 now"), "call [REDACTED] now");
+        assert_eq!(
+            redact(&[TOKEN_PATTERN], "token MTIzNDU2Nzg5MDEyMzQ1Njc4.GaBcDe.abcdefghijklmnopqrstuvwxyz0123 leaked"),
+            "token [REDACTED] leaked"
+        );
+    }
+
+    #[test]
+    fn test_redact_custom_pattern() {
+        assert_eq!(redact(&[r"sk-[A-Za-z0-9]{8,}"], "key sk-abcDEF123456 here"), "key [REDACTED] here");
+    }
+
+    #[test]
+    fn test_redact_leaves_non_matching_content() {
+        let content = "nothing secret here: 2025-12-16, version 1.2.3, @alice";
+        assert_eq!(redact(&[EMAIL_PATTERN, PHONE_PATTERN, TOKEN_PATTERN], content), content);
+    }
+
+    #[test]
+    fn test_redact_rejects_invalid_pattern() {
+        assert!(Redact::new(&["(unclosed".to_string()], String::new()).is_err());
+    }
+
+    #[test]
+    fn test_merge_identities_attributes_mapped_ids_to_one_author() {
+        let merge = MergeIdentities::from_json(r#"{"111": "alice", "222": "alice"}"#).unwrap();
+        let mut records: Vec<ExtractedRecord> = [("alice#1234", "222"), ("alice.new", "111"), ("bob", "333")]
+            .iter()
+            .map(|(username, user_id)| ExtractedRecord {
+                username: username.to_string(),
+                user_id: Some(user_id.to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        records.iter_mut().for_each(|record| merge.apply(record));
+
+        let roster = crate::roster::build_roster(&records);
+        let authors: Vec<(&str, Option<&str>)> =
+            roster.iter().map(|entry| (entry.username.as_str(), entry.user_id.as_deref())).collect();
+        assert_eq!(authors, vec![("alice", Some("111")), ("bob", Some("333"))]);
+        assert_eq!(crate::summary::build_summary(&records).authors["alice"], 2);
+        assert!(MergeIdentities::from_json("[1, 2]").is_err());
+    }
+
+    struct CapturingWriter(Arc<Mutex<Vec<String>>>);
+
+    impl MarkdownWriter for CapturingWriter {
+        fn write(&self, records: &[ExtractedRecord]) -> Result<()> {
+            let mut contents = self.0.lock().unwrap();
+            contents.extend(records.iter().map(|r| r.content.clone()));
+            Ok(())
+        }
+    }
+
+    fn flatten(keep_language: bool, content: &str) -> String {
+        let mut record = ExtractedRecord {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        FlattenCodeBlocks::new(keep_language).apply(&mut record);
+        record.content
+    }
+
+    #[test]
+    fn test_flatten_code_blocks() {
+        let content = "look:\n```rust\nfn main() {}\n```\nand ```inline``` too";
+        assert_eq!(flatten(false, content), "look:\nfn main() {}\nand inline too");
+        assert_eq!(flatten(true, content), "look:\n[rust]\nfn main() {}\nand inline too");
+        assert_eq!(flatten(false, "unclosed ```fence"), "unclosed ```fence");
+    }
+
+    #[test]
+    fn test_transformed_writer_only_affects_wrapped_writer() {
+        let records = vec![ExtractedRecord {
+            content: "```\nlet x = 1;\n```".to_string(),
+            ..Default::default()
+        }];
+        let plain = Arc::new(Mutex::new(Vec::new()));
+        let markdown = Arc::new(Mutex::new(Vec::new()));
+        let wrapped = TransformedWriter::new(
+            Box::new(CapturingWriter(plain.clone())),
+            vec![Box::new(FlattenCodeBlocks::new(false))],
+        );
+        let unwrapped = CapturingWriter(markdown.clone());
+
+        wrapped.write(&records).unwrap();
+        unwrapped.write(&records).unwrap();
+
+        assert_eq!(*plain.lock().unwrap(), vec!["let x = 1;"]);
+        assert_eq!(*markdown.lock().unwrap(), vec!["```\nlet x = 1;\n```"]);
+    }
+}
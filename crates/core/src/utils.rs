@@ -1,40 +1,436 @@
-use chrono::{Datelike, Local};
+use crate::domain::Reaction;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+#[cfg(feature = "timestamps")]
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, TimeZone, Utc, Weekday};
+#[cfg(feature = "timestamps")]
+use chrono_tz::Tz;
+#[cfg(feature = "timestamps")]
 use dateparser::parse;
 
+/// UTC offsets (in hours) for common North American and universal timezone abbreviations
+/// Abbreviations are ambiguous in general, so anything not listed is treated as UTC
+#[cfg(feature = "timestamps")]
+const TIMEZONE_ABBREVIATIONS: &[(&str, i32)] = &[
+    ("UTC", 0),
+    ("GMT", 0),
+    ("Z", 0),
+    ("EST", -5),
+    ("EDT", -4),
+    ("CST", -6),
+    ("CDT", -5),
+    ("MST", -7),
+    ("MDT", -6),
+    ("PST", -8),
+    ("PDT", -7),
+];
+
+/// Naive datetime layouts tried once a trailing timezone abbreviation is stripped
+#[cfg(feature = "timestamps")]
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parses a timestamp string into UTC
+/// Tries dateparser first; if that fails and the string ends in an alphabetic
+/// timezone token (e.g. `2025-12-16T10:30:00 PST`), the token is stripped and
+/// used as an offset hint for the remainder, falling back to UTC when unknown
+#[cfg(feature = "timestamps")]
+pub fn parse_timestamp(timestamp_str: &str) -> Option<DateTime<Utc>> {
+    if timestamp_str.is_empty() {
+        return None;
+    }
+    if let Ok(dt_utc) = parse(timestamp_str) {
+        return Some(dt_utc);
+    }
+
+    let (rest, abbreviation) = timestamp_str.trim().rsplit_once(' ')?;
+    if !abbreviation.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let offset_hours = TIMEZONE_ABBREVIATIONS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(abbreviation))
+        .map_or(0, |(_, hours)| *hours);
+    let offset = FixedOffset::east_opt(offset_hours * 3600)?;
+
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(rest.trim(), format).ok())?;
+    offset
+        .from_local_datetime(&naive)
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Timezone in which timestamps are shown and split into days, months and weekdays
+/// Without the `timestamps` feature nothing is converted, so only `Local` exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputTimezone {
+    /// The host's timezone (the `TZ` environment variable on Unix)
+    #[default]
+    Local,
+    /// An IANA timezone such as `America/New_York` or `UTC`, independent of the host
+    #[cfg(feature = "timestamps")]
+    Named(Tz),
+}
+
+#[cfg(feature = "timestamps")]
+impl OutputTimezone {
+    /// Converts a UTC time into this timezone and formats it with a chrono format string
+    pub fn format(self, dt_utc: DateTime<Utc>, format: &str) -> String {
+        match self {
+            OutputTimezone::Local => dt_utc.with_timezone(&Local).format(format).to_string(),
+            OutputTimezone::Named(tz) => dt_utc.with_timezone(&tz).format(format).to_string(),
+        }
+    }
+
+    /// Returns the day of the week of a UTC time in this timezone
+    pub fn weekday(self, dt_utc: DateTime<Utc>) -> Weekday {
+        match self {
+            OutputTimezone::Local => dt_utc.with_timezone(&Local).weekday(),
+            OutputTimezone::Named(tz) => dt_utc.with_timezone(&tz).weekday(),
+        }
+    }
+}
+
+impl FromStr for OutputTimezone {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("local") {
+            return Ok(OutputTimezone::Local);
+        }
+        #[cfg(feature = "timestamps")]
+        if let Ok(tz) = value.parse() {
+            return Ok(OutputTimezone::Named(tz));
+        }
+        Err(format!("unknown timezone '{}' (expected an IANA name such as America/New_York, UTC or local)", value))
+    }
+}
+
+/// Layout of converted timestamps; the numeric offset (rather than a zone abbreviation such as
+/// `CET`) keeps them parseable by `parse_timestamp`, so periods derived from them stay in the zone
+#[cfg(feature = "timestamps")]
+const LOCAL_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+/// Converts a timestamp string to Unix epoch seconds
+#[cfg(feature = "timestamps")]
+pub fn timestamp_to_epoch(timestamp_str: &str) -> Option<i64> {
+    parse_timestamp(timestamp_str).map(|dt_utc| dt_utc.timestamp())
+}
+
+/// Epoch conversion needs timestamp parsing, so this always returns None without the feature
+#[cfg(not(feature = "timestamps"))]
+pub fn timestamp_to_epoch(_timestamp_str: &str) -> Option<i64> {
+    None
+}
+
+/// Converts a timestamp string to Unix epoch milliseconds, keeping any fraction of a second
+#[cfg(feature = "timestamps")]
+pub fn timestamp_to_epoch_millis(timestamp_str: &str) -> Option<i64> {
+    parse_timestamp(timestamp_str).map(|dt_utc| dt_utc.timestamp_millis())
+}
+
+/// Epoch conversion needs timestamp parsing, so this always returns None without the feature
+#[cfg(not(feature = "timestamps"))]
+pub fn timestamp_to_epoch_millis(_timestamp_str: &str) -> Option<i64> {
+    None
+}
+
 /// Parses a timestamp string and converts it to local timezone
+pub fn format_timestamp_to_local(timestamp_str: &str) -> String {
+    format_timestamp_in(timestamp_str, OutputTimezone::Local)
+}
+
+/// Parses a timestamp string and converts it to the given timezone
 /// Supports various formats: ISO 8601, SQLite datetime, etc.
 /// Uses dateparser to automatically detect and parse common date formats
-pub fn format_timestamp_to_local(timestamp_str: &str) -> String {
+#[cfg(feature = "timestamps")]
+pub fn format_timestamp_in(timestamp_str: &str, timezone: OutputTimezone) -> String {
     if timestamp_str.is_empty() {
         return String::new();
     }
 
-    match parse(timestamp_str) {
-        Ok(dt_utc) => {
-            let local_dt = dt_utc.with_timezone(&Local);
-            local_dt.format("%Y-%m-%d %H:%M:%S %Z").to_string()
-        }
-        Err(_) => {
+    match parse_timestamp(timestamp_str) {
+        Some(dt_utc) => timezone.format(dt_utc, LOCAL_TIMESTAMP_FORMAT),
+        None => {
             // If parsing fails, return the original string
             timestamp_str.to_string()
         }
     }
 }
 
-/// Extracts year-month (YYYY-MM) from a timestamp string
+/// Returns the timestamp unchanged when the `timestamps` feature is disabled
+#[cfg(not(feature = "timestamps"))]
+pub fn format_timestamp_in(timestamp_str: &str, _timezone: OutputTimezone) -> String {
+    timestamp_str.to_string()
+}
+
+/// Milliseconds between the Unix epoch and the Discord epoch (2015-01-01T00:00:00Z)
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// Derives the creation time of a Discord snowflake id, formatted like `format_timestamp_in`
+/// Returns None for ids that are not numeric snowflakes
+#[cfg(feature = "timestamps")]
+pub fn snowflake_timestamp(id: &str, timezone: OutputTimezone) -> Option<String> {
+    let snowflake: u64 = id.trim().parse().ok()?;
+    let millis = (snowflake >> 22).checked_add(DISCORD_EPOCH_MS)?;
+    let dt_utc = DateTime::<Utc>::from_timestamp_millis(i64::try_from(millis).ok()?)?;
+    Some(timezone.format(dt_utc, LOCAL_TIMESTAMP_FORMAT))
+}
+
+/// Derives the creation time of a Discord snowflake id as an RFC 3339 UTC string
+/// Used when the `timestamps` feature is disabled
+#[cfg(not(feature = "timestamps"))]
+pub fn snowflake_timestamp(id: &str, _timezone: OutputTimezone) -> Option<String> {
+    let snowflake: u64 = id.trim().parse().ok()?;
+    let secs = ((snowflake >> 22).checked_add(DISCORD_EPOCH_MS)?) / 1000;
+    Some(format_epoch_utc(i64::try_from(secs).ok()?))
+}
+
+/// Formats Unix epoch seconds as an RFC 3339 UTC string, e.g. `2025-12-16T10:30:00Z`
+/// Computed by hand so it is available without the `timestamps` feature
+pub fn format_epoch_utc(secs: i64) -> String {
+    let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil-from-days conversion for the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Formats Unix epoch milliseconds like `format_epoch_utc`, adding the milliseconds when there
+/// are any, e.g. `2025-12-16T10:30:00.250Z`
+pub fn format_epoch_millis_utc(millis: i64) -> String {
+    let (secs, millis) = (millis.div_euclid(1000), millis.rem_euclid(1000));
+    let formatted = format_epoch_utc(secs);
+    match millis {
+        0 => formatted,
+        _ => format!("{}.{:03}Z", formatted.trim_end_matches('Z'), millis),
+    }
+}
+
+/// Resolves the time stamped into outputs as `generated_at`, as an RFC 3339 UTC string
+/// An explicit `now` wins over `source_date_epoch` (Unix seconds, as in the reproducible-builds
+/// `SOURCE_DATE_EPOCH` convention); with neither, the real current time is used
+pub fn resolve_generated_at(now: Option<&str>, source_date_epoch: Option<&str>) -> Result<String, String> {
+    if let Some(now) = now {
+        return timestamp_to_epoch(now)
+            .map(format_epoch_utc)
+            .ok_or_else(|| format!("cannot parse '{}' as a timestamp", now));
+    }
+    if let Some(epoch) = source_date_epoch {
+        return epoch
+            .trim()
+            .parse()
+            .map(format_epoch_utc)
+            .map_err(|_| format!("SOURCE_DATE_EPOCH '{}' is not a number of seconds", epoch));
+    }
+    let elapsed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?;
+    Ok(format_epoch_utc(elapsed.as_secs() as i64))
+}
+
+/// Extracts year-month (YYYY-MM) from a timestamp string in the local timezone
+pub fn extract_year_month(timestamp: &str) -> Option<String> {
+    extract_year_month_in(timestamp, OutputTimezone::Local)
+}
+
+/// Extracts year-month (YYYY-MM) from a timestamp string in the given timezone
 /// Supports formats like: "2025-12-16 10:30:00 PST", "2025-12-16T10:30:00", etc.
 /// Uses dateparser to automatically detect and parse common date formats
-pub fn extract_year_month(timestamp: &str) -> Option<String> {
+#[cfg(feature = "timestamps")]
+pub fn extract_year_month_in(timestamp: &str, timezone: OutputTimezone) -> Option<String> {
+    if timestamp.is_empty() {
+        return None;
+    }
+
+    parse_timestamp(timestamp).map(|dt_utc| timezone.format(dt_utc, "%Y-%m"))
+}
+
+/// Extracts year-month (YYYY-MM) from a leading `YYYY-MM` prefix
+/// Used when the `timestamps` feature is disabled and no parsing is available
+#[cfg(not(feature = "timestamps"))]
+pub fn extract_year_month_in(timestamp: &str, _timezone: OutputTimezone) -> Option<String> {
+    let prefix = timestamp.get(..7)?;
+    let bytes = prefix.as_bytes();
+    let is_year_month = bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..].iter().all(u8::is_ascii_digit);
+
+    is_year_month.then(|| prefix.to_string())
+}
+
+/// Validates a calendar day given as `YYYY-MM-DD`, e.g. for a date filter
+pub fn parse_day(value: &str) -> Result<String, String> {
+    let error = || format!("invalid date '{}' (expected YYYY-MM-DD)", value);
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(error());
+    };
+    let number = |part: &str, len: usize| {
+        (part.len() == len && part.bytes().all(|b| b.is_ascii_digit())).then(|| part.parse::<u32>().ok()).flatten()
+    };
+    match (number(year, 4), number(month, 2), number(day, 2)) {
+        (Some(_), Some(1..=12), Some(1..=31)) => Ok(value.to_string()),
+        _ => Err(error()),
+    }
+}
+
+/// Extracts the local calendar date (YYYY-MM-DD) from a timestamp string
+pub fn extract_date(timestamp: &str) -> Option<String> {
+    extract_date_in(timestamp, OutputTimezone::Local)
+}
+
+/// Extracts the calendar date (YYYY-MM-DD) of a timestamp in the given timezone
+/// Uses the same parsing and timezone conversion as `extract_year_month_in`
+#[cfg(feature = "timestamps")]
+pub fn extract_date_in(timestamp: &str, timezone: OutputTimezone) -> Option<String> {
+    if timestamp.is_empty() {
+        return None;
+    }
+
+    parse_timestamp(timestamp).map(|dt_utc| timezone.format(dt_utc, "%Y-%m-%d"))
+}
+
+/// Extracts the date (YYYY-MM-DD) from a leading prefix
+/// Used when the `timestamps` feature is disabled and no parsing is available
+#[cfg(not(feature = "timestamps"))]
+pub fn extract_date_in(timestamp: &str, _timezone: OutputTimezone) -> Option<String> {
+    let prefix = timestamp.get(..10)?;
+    let month = extract_year_month(prefix)?;
+    let bytes = prefix.as_bytes();
+    let is_date = bytes[7] == b'-' && bytes[8..].iter().all(u8::is_ascii_digit);
+
+    is_date.then(|| format!("{}{}", month, &prefix[7..]))
+}
+
+/// Length of time covered by one output period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Granularity {
+    /// `YYYY-MM-DD`
+    Day,
+    /// `YYYY-MM`
+    #[default]
+    Month,
+    /// `YYYY`
+    Year,
+}
+
+impl Granularity {
+    /// Lowercase name, as accepted by `FromStr`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "day" => Ok(Self::Day),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            other => Err(format!("unknown granularity '{}' (expected day, month or year)", other)),
+        }
+    }
+}
+
+/// Extracts the local period label of a timestamp at the given granularity
+pub fn extract_period(timestamp: &str, granularity: Granularity) -> Option<String> {
+    extract_period_in(timestamp, granularity, OutputTimezone::Local)
+}
+
+/// Extracts the period label of a timestamp at the given granularity in the given timezone
+/// Built on `extract_date_in` and `extract_year_month_in`, so parsing and timezone handling match
+pub fn extract_period_in(timestamp: &str, granularity: Granularity, timezone: OutputTimezone) -> Option<String> {
+    match granularity {
+        Granularity::Day => extract_date_in(timestamp, timezone),
+        Granularity::Month => extract_year_month_in(timestamp, timezone),
+        Granularity::Year => {
+            extract_year_month_in(timestamp, timezone).map(|year_month| year_month[..4].to_string())
+        }
+    }
+}
+
+/// Extracts the lowercase English weekday name (e.g. "monday") from a timestamp string
+/// in the local timezone
+pub fn extract_weekday(timestamp: &str) -> Option<String> {
+    extract_weekday_in(timestamp, OutputTimezone::Local)
+}
+
+/// Extracts the lowercase English weekday name of a timestamp in the given timezone
+/// Uses the same parsing and timezone conversion as `extract_year_month_in`
+#[cfg(feature = "timestamps")]
+pub fn extract_weekday_in(timestamp: &str, timezone: OutputTimezone) -> Option<String> {
     if timestamp.is_empty() {
         return None;
     }
 
-    parse(timestamp).ok().map(|dt_utc| {
-        let local_dt = dt_utc.with_timezone(&Local);
-        format!("{:04}-{:02}", local_dt.year(), local_dt.month())
+    parse_timestamp(timestamp).map(|dt_utc| {
+        let name = match timezone.weekday(dt_utc) {
+            Weekday::Mon => "monday",
+            Weekday::Tue => "tuesday",
+            Weekday::Wed => "wednesday",
+            Weekday::Thu => "thursday",
+            Weekday::Fri => "friday",
+            Weekday::Sat => "saturday",
+            Weekday::Sun => "sunday",
+        };
+        name.to_string()
     })
 }
 
+/// Weekdays cannot be derived without timestamp parsing, so this always returns None
+#[cfg(not(feature = "timestamps"))]
+pub fn extract_weekday_in(_timestamp: &str, _timezone: OutputTimezone) -> Option<String> {
+    None
+}
+
+/// Returns the number of seconds from `earlier` to `later` (negative if out of order)
+/// Returns None when either timestamp cannot be parsed
+#[cfg(feature = "timestamps")]
+pub fn seconds_between(earlier: &str, later: &str) -> Option<i64> {
+    let earlier = parse_timestamp(earlier)?;
+    let later = parse_timestamp(later)?;
+    Some((later - earlier).num_seconds())
+}
+
+/// Time differences cannot be computed without timestamp parsing, so this always returns None
+#[cfg(not(feature = "timestamps"))]
+pub fn seconds_between(_earlier: &str, _later: &str) -> Option<i64> {
+    None
+}
+
 /// Sanitizes a string for use in a filename
 /// Replaces invalid filename characters with hyphens
 pub fn sanitize_filename(name: &str) -> String {
@@ -49,6 +445,243 @@ pub fn sanitize_filename(name: &str) -> String {
         .to_string()
 }
 
+/// Splits a raw `attachments` column into individual entries
+/// Accepts a JSON array of strings, or entries separated by newlines or by commas before a URL
+/// (commas inside a URL are kept); blank entries are dropped.
+/// The browser crawler only stores a `Yes` marker, which yields a single entry
+pub fn parse_attachments(raw: &str) -> Vec<String> {
+    let raw = raw.trim();
+    if raw.starts_with('[') {
+        if let Ok(entries) = serde_json::from_str::<Vec<String>>(raw) {
+            return entries.into_iter().filter(|entry| !entry.trim().is_empty()).collect();
+        }
+    }
+
+    let mut entries: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        let mut line_entries: Vec<String> = Vec::new();
+        for piece in line.split(',') {
+            let is_url = piece.trim_start().starts_with("http://") || piece.trim_start().starts_with("https://");
+            match line_entries.last_mut() {
+                Some(entry) if !is_url => {
+                    entry.push(',');
+                    entry.push_str(piece);
+                }
+                _ => line_entries.push(piece.to_string()),
+            }
+        }
+        entries.extend(line_entries);
+    }
+    entries
+        .into_iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Parses a raw `reactions` column: a JSON array of `{"emoji": ..., "count": ...}` objects
+/// Anything else, including malformed JSON, yields no reactions
+pub fn parse_reactions(raw: &str) -> Vec<Reaction> {
+    serde_json::from_str(raw.trim()).unwrap_or_default()
+}
+
+/// Removes control characters from content, keeping newlines, carriage returns and tabs
+/// Covers C0 controls such as bell (`\x07`) and vertical tab (`\x0b`), DEL and C1 controls
+pub fn strip_control_chars(content: &str) -> String {
+    content
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .collect()
+}
+
+/// Formats a byte count for humans, e.g. `512 B`, `1.5 KiB` or `2.0 MiB`
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Describes a time offset in seconds for humans, e.g. `3 months ago`, or `in 2 days` when negative
+/// Months are 30 days and years 365, which is close enough at that scale
+pub fn humanize_ago(seconds: i64) -> String {
+    const UNITS: [(&str, i64); 6] = [
+        ("year", 365 * 86_400),
+        ("month", 30 * 86_400),
+        ("day", 86_400),
+        ("hour", 3600),
+        ("minute", 60),
+        ("second", 1),
+    ];
+    let magnitude = seconds.abs();
+    if magnitude < 45 {
+        return "just now".to_string();
+    }
+    let (unit, size) = UNITS
+        .iter()
+        .find(|(_, size)| magnitude >= *size)
+        .copied()
+        .unwrap_or(UNITS[UNITS.len() - 1]);
+    let count = magnitude / size;
+    let amount = format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" });
+    if seconds < 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+/// Counts words using Unicode word segmentation (UAX #29)
+/// Punctuation and whitespace are not words, contractions and decimals stay whole,
+/// and each CJK ideograph counts as one word
+pub fn count_words(content: &str) -> usize {
+    content.unicode_words().count()
+}
+
+/// Whether a character is an emoji (pictographs, dingbats, symbols and regional indicators)
+fn is_emoji_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B05..=0x2B07 | 0x2B1B..=0x2B1C | 0x2B50 | 0x2B55 | 0x3030 | 0x303D
+    )
+}
+
+/// Counts emoji in message content
+/// Each grapheme cluster holding an emoji counts once, so skin tones, ZWJ sequences
+/// and flags are single emoji; Discord custom emoji (`<:name:id>`, `<a:name:id>`) count too
+pub fn count_emoji(content: &str) -> usize {
+    let unicode = content
+        .graphemes(true)
+        .filter(|grapheme| grapheme.chars().any(is_emoji_char))
+        .count();
+    let custom = content
+        .split('<')
+        .skip(1)
+        .filter(|rest| {
+            let Some((tag, _)) = rest.split_once('>') else {
+                return false;
+            };
+            let tag = tag.strip_prefix('a').unwrap_or(tag);
+            let mut parts = tag.splitn(3, ':');
+            matches!(
+                (parts.next(), parts.next(), parts.next()),
+                (Some(""), Some(name), Some(id))
+                    if !name.is_empty() && !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+            )
+        })
+        .count();
+    unicode + custom
+}
+
+/// Normalizes message content for duplicate detection: trimmed, lowercased, whitespace collapsed
+pub fn normalize_content(content: &str) -> String {
+    content.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Hashes the normalized content of a message (see `normalize_content`)
+/// Hashes are only comparable within a single run
+pub fn content_hash(content: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    normalize_content(content).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Counts `http://` and `https://` links in message content (see `extract_links`)
+pub fn count_links(content: &str) -> usize {
+    extract_links(content).len()
+}
+
+/// Extracts `http://` and `https://` links from message content, in order
+/// A scheme only counts at the start of a word, so `xhttp://` is not a link. A link ends at
+/// whitespace or a closing `>`, and trailing sentence punctuation is not part of it
+pub fn extract_links(content: &str) -> Vec<String> {
+    content
+        .match_indices("http")
+        .filter_map(|(start, _)| {
+            let rest = &content[start + 4..];
+            let at_word_start = !content[..start].chars().next_back().is_some_and(char::is_alphanumeric);
+            let scheme_len = if rest.starts_with("://") {
+                3
+            } else if rest.starts_with("s://") {
+                4
+            } else {
+                return None;
+            };
+            if !at_word_start {
+                return None;
+            }
+            let link = &content[start..];
+            let end = link.find(|c: char| c.is_whitespace() || c == '>').unwrap_or(link.len());
+            let link = link[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+            // Drop a closing parenthesis that belongs to surrounding text or Markdown link syntax
+            let link = if link.ends_with(')') && !link.contains('(') {
+                &link[..link.len() - 1]
+            } else {
+                link
+            };
+            (link.len() > 4 + scheme_len).then(|| link.to_string())
+        })
+        .collect()
+}
+
+/// Soft-wraps content so no line exceeds `width` display columns
+/// Breaks only at whitespace (words longer than `width` stay intact), preserves
+/// existing newlines and leading indentation, and leaves fenced code blocks untouched.
+/// A width of 0 disables wrapping.
+pub fn wrap_content(content: &str, width: usize) -> String {
+    if width == 0 {
+        return content.to_string();
+    }
+
+    let mut wrapped: Vec<String> = Vec::new();
+    let mut in_code_fence = false;
+
+    for line in content.split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_fence = !in_code_fence;
+            wrapped.push(line.to_string());
+            continue;
+        }
+        if in_code_fence || line.width() <= width {
+            wrapped.push(line.to_string());
+            continue;
+        }
+
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let mut current = indent.to_string();
+        let mut current_width = indent.width();
+        let mut has_word = false;
+
+        for word in line.split_whitespace() {
+            let word_width = word.width();
+            if has_word && current_width + 1 + word_width > width {
+                wrapped.push(std::mem::take(&mut current));
+                current_width = 0;
+                has_word = false;
+            }
+            if has_word {
+                current.push(' ');
+                current_width += 1;
+            }
+            current.push_str(word);
+            current_width += word_width;
+            has_word = true;
+        }
+        wrapped.push(current);
+    }
+
+    wrapped.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +727,67 @@ mod tests {
         assert_eq!(format_timestamp_to_local(invalid), invalid);
     }
 
+    #[cfg(feature = "timestamps")]
+    fn utc(timestamp: &str) -> Option<String> {
+        parse_timestamp(timestamp).map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_parse_timestamp_abbreviation_with_space_separator() {
+        assert_eq!(utc("2025-12-16 10:30:00 PST"), Some("2025-12-16 18:30:00".to_string()));
+        assert_eq!(utc("2025-12-16 10:30:00 UTC"), Some("2025-12-16 10:30:00".to_string()));
+        assert_eq!(utc("2025-12-16 10:30:00 GMT"), Some("2025-12-16 10:30:00".to_string()));
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_parse_timestamp_abbreviation_with_t_separator() {
+        assert_eq!(utc("2025-12-16T10:30:00 PST"), Some("2025-12-16 18:30:00".to_string()));
+        assert_eq!(utc("2025-12-16T10:30:00 UTC"), Some("2025-12-16 10:30:00".to_string()));
+        assert_eq!(utc("2025-12-16T10:30:00 GMT"), Some("2025-12-16 10:30:00".to_string()));
+        assert_eq!(utc("2025-12-16T10:30:00 pdt"), Some("2025-12-16 17:30:00".to_string()));
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_parse_timestamp_unknown_abbreviation_falls_back_to_utc() {
+        assert_eq!(utc("2025-12-16T10:30:00 XYZ"), Some("2025-12-16 10:30:00".to_string()));
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_parse_timestamp_rejects_garbage() {
+        assert_eq!(utc(""), None);
+        assert_eq!(utc("not a timestamp"), None);
+        assert_eq!(utc("2025-12-16T10:30:00 +0x"), None);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_timestamp_to_epoch() {
+        assert_eq!(timestamp_to_epoch("2025-12-16T10:30:00Z"), Some(1_765_881_000));
+        assert_eq!(timestamp_to_epoch("2025-12-16 02:30:00 PST"), Some(1_765_881_000));
+        assert_eq!(timestamp_to_epoch("not-a-timestamp"), None);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_format_timestamp_to_local_with_abbreviations() {
+        for input in ["2025-12-16T10:30:00 PST", "2025-12-16T10:30:00 UTC", "2025-12-16T10:30:00 GMT"] {
+            let result = format_timestamp_to_local(input);
+            assert_ne!(result, input);
+            assert!(result.starts_with("2025-12-1"));
+        }
+    }
+
+    #[test]
+    fn test_snowflake_timestamp() {
+        // Snowflake from Discord's documentation: 2016-04-30 11:18:25.796 UTC
+        let timestamp = snowflake_timestamp("175928847299117063", OutputTimezone::Local).unwrap();
+        assert_eq!(extract_year_month(&timestamp), Some("2016-04".to_string()));
+        assert_eq!(snowflake_timestamp("not-a-snowflake", OutputTimezone::Local), None);
+    }
 
     #[test]
     fn test_extract_year_month_valid_format_with_space() {
@@ -134,6 +828,92 @@ mod tests {
         assert_eq!(extract_year_month(""), None);
     }
 
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_output_timezone_shifts_time_and_month() {
+        let utc: OutputTimezone = "UTC".parse().unwrap();
+        let new_york: OutputTimezone = "America/New_York".parse().unwrap();
+        let timestamp = "2025-12-01T02:30:00Z";
+
+        assert_eq!(format_timestamp_in(timestamp, utc), "2025-12-01 02:30:00 +00:00");
+        assert_eq!(format_timestamp_in(timestamp, new_york), "2025-11-30 21:30:00 -05:00");
+        assert_eq!(extract_year_month_in(timestamp, utc), Some("2025-12".to_string()));
+        assert_eq!(extract_year_month_in(timestamp, new_york), Some("2025-11".to_string()));
+        // The converted string names its own offset, so it still falls in the same month
+        assert_eq!(
+            extract_year_month_in(&format_timestamp_in(timestamp, new_york), new_york),
+            Some("2025-11".to_string())
+        );
+        assert_eq!("Local".parse(), Ok(OutputTimezone::Local));
+        assert!("Mars/Olympus".parse::<OutputTimezone>().is_err());
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_year_month_comes_from_the_instant_not_the_text() {
+        let utc: OutputTimezone = "UTC".parse().unwrap();
+        let helsinki: OutputTimezone = "Europe/Helsinki".parse().unwrap();
+        // 2025-12-31T23:30:00Z, written in a zone where it is already January
+        let timestamp = "2026-01-01 01:30:00 +02:00";
+
+        assert_eq!(extract_year_month_in(timestamp, utc), Some("2025-12".to_string()));
+        assert_eq!(extract_year_month_in(timestamp, helsinki), Some("2026-01".to_string()));
+        assert_eq!(extract_year_month_in("2025-12-31T23:30:00Z", helsinki), Some("2026-01".to_string()));
+    }
+
+    #[test]
+    fn test_extract_date() {
+        assert_eq!(extract_date("2025-12-16 10:30:00"), Some("2025-12-16".to_string()));
+        assert_eq!(extract_date("2025-12"), None);
+        assert_eq!(extract_date("invalid"), None);
+    }
+
+    #[test]
+    fn test_extract_period() {
+        let timestamp = "2025-12-16 10:30:00";
+        assert_eq!(extract_period(timestamp, Granularity::Day), Some("2025-12-16".to_string()));
+        assert_eq!(extract_period(timestamp, Granularity::Month), Some("2025-12".to_string()));
+        assert_eq!(extract_period(timestamp, Granularity::Year), Some("2025".to_string()));
+        assert_eq!(extract_period("invalid", Granularity::Year), None);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_extract_weekday_known_dates() {
+        assert_eq!(extract_weekday("2025-12-15 12:00:00"), Some("monday".to_string()));
+        assert_eq!(extract_weekday("2025-12-20 12:00:00"), Some("saturday".to_string()));
+        assert_eq!(extract_weekday("2025-12-21 12:00:00"), Some("sunday".to_string()));
+    }
+
+    #[test]
+    fn test_extract_weekday_invalid() {
+        assert_eq!(extract_weekday("invalid"), None);
+        assert_eq!(extract_weekday(""), None);
+    }
+
+    #[cfg(not(feature = "timestamps"))]
+    #[test]
+    fn test_format_timestamp_to_local_passthrough_without_feature() {
+        assert_eq!(format_timestamp_to_local("2025-12-16T10:30:00Z"), "2025-12-16T10:30:00Z");
+        assert_eq!(format_timestamp_to_local("2025-12-16 10:30:00 PST"), "2025-12-16 10:30:00 PST");
+        assert_eq!(format_timestamp_to_local("not-a-timestamp"), "not-a-timestamp");
+    }
+
+    #[cfg(not(feature = "timestamps"))]
+    #[test]
+    fn test_extract_year_month_prefix_without_feature() {
+        assert_eq!(extract_year_month("2025-12-16T10:30:00Z"), Some("2025-12".to_string()));
+        assert_eq!(extract_year_month("12/16/2025"), None);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_seconds_between() {
+        assert_eq!(seconds_between("2025-12-16 10:30:00 UTC", "2025-12-16 10:35:30 UTC"), Some(330));
+        assert_eq!(seconds_between("2025-12-16 10:30:00 PST", "2025-12-16 18:30:00 UTC"), Some(0));
+        assert_eq!(seconds_between("invalid", "2025-12-16 10:30:00 UTC"), None);
+    }
+
     #[test]
     fn test_sanitize_filename_valid() {
         assert_eq!(sanitize_filename("general"), "general");
@@ -175,5 +955,194 @@ mod tests {
     fn test_sanitize_filename_empty() {
         assert_eq!(sanitize_filename(""), "");
     }
-}
 
+    #[test]
+    fn test_parse_attachments() {
+        assert_eq!(parse_attachments(""), Vec::<String>::new());
+        assert_eq!(parse_attachments("Yes"), vec!["Yes"]);
+        assert_eq!(parse_attachments("a.png\n\n b.png "), vec!["a.png", "b.png"]);
+        assert_eq!(parse_attachments(r#"["https://cdn/a.png", ""]"#), vec!["https://cdn/a.png"]);
+        assert_eq!(
+            parse_attachments("https://cdn/a.png, https://cdn/b.png,https://cdn/c?size=1,2"),
+            vec!["https://cdn/a.png", "https://cdn/b.png", "https://cdn/c?size=1,2"]
+        );
+        assert_eq!(parse_attachments("Yes, really"), vec!["Yes, really"]);
+    }
+
+    #[test]
+    fn test_parse_reactions() {
+        let reactions = parse_reactions(r#"[{"emoji": "👍", "count": 3}, {"emoji": "fire", "count": 1}]"#);
+        assert_eq!(
+            reactions,
+            vec![
+                Reaction {
+                    emoji: "👍".to_string(),
+                    count: 3
+                },
+                Reaction {
+                    emoji: "fire".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert!(parse_reactions("").is_empty());
+        assert!(parse_reactions("not json").is_empty());
+    }
+
+    #[test]
+    fn test_strip_control_chars_removes_bell_and_vertical_tab() {
+        assert_eq!(strip_control_chars("ding\x07 dong\x0bdone"), "ding dongdone");
+    }
+
+    #[test]
+    fn test_strip_control_chars_keeps_whitespace_and_text() {
+        let content = "line one\r\n\tindented\nüñí 日本語";
+        assert_eq!(strip_control_chars(content), content);
+        assert_eq!(strip_control_chars("del\x7f c1\u{9b}"), "del c1");
+    }
+
+    #[test]
+    fn test_count_words_english() {
+        assert_eq!(count_words("The quick brown fox can't jump 3.5 meters"), 8);
+        assert_eq!(count_words(""), 0);
+    }
+
+    #[test]
+    fn test_count_words_cjk() {
+        assert_eq!(count_words("日本語"), 3);
+        assert_eq!(count_words("hello 世界"), 3);
+    }
+
+    #[test]
+    fn test_count_words_ignores_punctuation() {
+        assert_eq!(count_words("Wait... what?!? -- really!!"), 3);
+        assert_eq!(count_words("--- *** ... !!!"), 0);
+        assert_eq!(count_words("e-mail, well-known; (yes)"), 5);
+    }
+
+    #[test]
+    fn test_extract_links() {
+        assert_eq!(
+            extract_links("see https://example.com/a?b=1, and (http://test.io/x).\nhttps://cdn.discordapp.com/f.png!"),
+            vec!["https://example.com/a?b=1", "http://test.io/x", "https://cdn.discordapp.com/f.png"]
+        );
+        assert_eq!(
+            extract_links("[docs](https://docs.rs) <https://en.wikipedia.org/wiki/Rust_(language)>"),
+            vec!["https://docs.rs", "https://en.wikipedia.org/wiki/Rust_(language)"]
+        );
+        assert!(extract_links("no links, just https:// and http text").is_empty());
+    }
+
+    #[test]
+    fn test_content_hash_ignores_case_and_whitespace() {
+        assert_eq!(normalize_content("  Buy   NOW\n cheap "), "buy now cheap");
+        assert_eq!(content_hash("Buy now cheap"), content_hash("  buy\tNOW   cheap\n"));
+        assert_ne!(content_hash("buy now cheap"), content_hash("buy now, cheap"));
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day("2025-12-16"), Ok("2025-12-16".to_string()));
+        for invalid in ["2025-13-01", "2025-12-1", "2025/12/16", "16-12-2025", "2025-12-16T00", ""] {
+            assert!(parse_day(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn test_format_epoch_utc() {
+        assert_eq!(format_epoch_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_epoch_utc(1_765_881_000), "2025-12-16T10:30:00Z");
+        assert_eq!(format_epoch_millis_utc(1_765_881_000_000), "2025-12-16T10:30:00Z");
+        assert_eq!(format_epoch_millis_utc(1_765_881_000_250), "2025-12-16T10:30:00.250Z");
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_resolve_generated_at() {
+        let fixed = resolve_generated_at(Some("2025-12-16T10:30:00Z"), Some("0")).unwrap();
+        assert_eq!(fixed, "2025-12-16T10:30:00Z");
+        assert_eq!(resolve_generated_at(None, Some("1765881000")).unwrap(), fixed);
+        assert!(resolve_generated_at(Some("not a time"), None).is_err());
+        assert!(resolve_generated_at(None, Some("soon")).is_err());
+        assert!(resolve_generated_at(None, None).unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn test_humanize_ago() {
+        assert_eq!(humanize_ago(10), "just now");
+        assert_eq!(humanize_ago(60), "1 minute ago");
+        assert_eq!(humanize_ago(5 * 3600 + 59), "5 hours ago");
+        assert_eq!(humanize_ago(92 * 86_400), "3 months ago");
+        assert_eq!(humanize_ago(800 * 86_400), "2 years ago");
+        assert_eq!(humanize_ago(-2 * 86_400), "in 2 days");
+    }
+
+    #[test]
+    fn test_count_emoji() {
+        assert_eq!(count_emoji("great job 🎉🎉 ❤️"), 3);
+        assert_eq!(count_emoji("👍🏽 and 👨‍👩‍👧 and 🇫🇷"), 3);
+        assert_eq!(count_emoji("custom <:pepe:123456> and <a:dance:789>"), 2);
+        assert_eq!(count_emoji("no emoji here <@123> <#456> :smile:"), 0);
+    }
+
+    #[test]
+    fn test_count_links() {
+        assert_eq!(count_links("see https://example.com and http://test.io/x?y=1"), 2);
+        assert_eq!(count_links("[docs](https://docs.rs) <https://crates.io>"), 2);
+        assert_eq!(count_links("xhttps://nope, http:/broken, https in prose"), 0);
+    }
+
+    #[test]
+    fn test_wrap_content_long_line() {
+        let content = "the quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            wrap_content(content, 15),
+            "the quick brown\nfox jumps over\nthe lazy dog"
+        );
+    }
+
+    #[test]
+    fn test_wrap_content_preserves_short_lines_and_newlines() {
+        let content = "short line\n\nanother short one\n";
+        assert_eq!(wrap_content(content, 20), content);
+    }
+
+    #[test]
+    fn test_wrap_content_does_not_break_words() {
+        assert_eq!(
+            wrap_content("a supercalifragilistic word", 10),
+            "a\nsupercalifragilistic\nword"
+        );
+    }
+
+    #[test]
+    fn test_wrap_content_skips_code_fences() {
+        let long_code = "let value = some_function(argument_one, argument_two, argument_three);";
+        let content = format!("```rust\n{}\n```\nafter the fence this prose wraps", long_code);
+
+        let wrapped = wrap_content(&content, 20);
+
+        assert!(wrapped.contains(long_code));
+        assert!(wrapped.ends_with("after the fence this\nprose wraps"));
+    }
+
+    #[test]
+    fn test_wrap_content_uses_display_width() {
+        // Each CJK character occupies two columns
+        assert_eq!(wrap_content("日本語 日本語 日本語", 14), "日本語 日本語\n日本語");
+    }
+
+    #[test]
+    fn test_wrap_content_zero_width_disables() {
+        let content = "no wrapping at all here";
+        assert_eq!(wrap_content(content, 0), content);
+    }
+}